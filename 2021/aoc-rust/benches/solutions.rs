@@ -0,0 +1,6 @@
+use aoc_rust_2021::{inputs, SOLUTIONS};
+
+fn main() {
+    let history = concat!(env!("CARGO_MANIFEST_DIR"), "/bench-history.json");
+    aoc_common::bench::main(2021, SOLUTIONS, &inputs(), history.as_ref());
+}
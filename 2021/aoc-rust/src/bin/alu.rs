@@ -0,0 +1,406 @@
+//! Steps through a day 24 ALU program interactively.
+//!
+//! Usage: `alu FILE [DIGITS]`
+//!
+//! Loads the program from FILE, queues DIGITS as its input if they are given, and reads
+//! commands from stdin:
+//!
+//! ```text
+//! step [N]       evaluate the next N instructions, printing the registers after each
+//! continue       run until a breakpoint, the end of the program, or an inp with no input
+//! break N        stop before evaluating instruction N
+//! delete N       remove the breakpoint on instruction N
+//! input DIGITS   queue more input, such as `13579` or `1 3 -5` for multi-digit values
+//! registers      print the registers
+//! list [N]       list the instructions around N, or around the next instruction
+//! trace          toggle printing the registers after every instruction while continuing
+//! reset          restart the program and drop any queued input
+//! quit
+//! ```
+//!
+//! Every command can be shortened to its first letter, and an empty line repeats the
+//! last command.
+
+use aoc_rust_2021::day24::{Instruction, Monad};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    env, fs,
+    io::{self, BufRead, IsTerminal, Write},
+    process,
+};
+
+/// Why the program stopped running.
+enum Stop {
+    Breakpoint,
+    End,
+    NeedsInput,
+    Invalid,
+}
+
+struct Debugger<'a> {
+    instructions: &'a [Instruction],
+    monad: Monad<'a>,
+    input: VecDeque<i64>,
+    breakpoints: BTreeSet<usize>,
+    /// Whether the program last stopped at the breakpoint on the next instruction, which
+    /// it should run past when it resumes.
+    at_breakpoint: bool,
+    trace: bool,
+}
+
+impl<'a> Debugger<'a> {
+    fn new(instructions: &'a [Instruction]) -> Self {
+        Self {
+            instructions,
+            monad: Monad::new(instructions),
+            input: VecDeque::new(),
+            breakpoints: BTreeSet::new(),
+            at_breakpoint: false,
+            trace: false,
+        }
+    }
+
+    fn write_registers(&self, out: &mut impl Write) -> io::Result<()> {
+        let [w, x, y, z] = self.monad.registers();
+        writeln!(out, "w={} x={} y={} z={}", w, x, y, z)
+    }
+
+    fn write_step(
+        &self,
+        out: &mut impl Write,
+        index: usize,
+        instruction: Instruction,
+    ) -> io::Result<()> {
+        write!(out, "{:>5}  {:<12}  ", index, instruction.to_string())?;
+        self.write_registers(out)
+    }
+
+    fn write_stop(&self, out: &mut impl Write, stop: Stop) -> io::Result<()> {
+        let counter = self.monad.counter();
+        match stop {
+            Stop::Breakpoint => writeln!(out, "breakpoint at instruction {}", counter),
+            Stop::End => {
+                write!(out, "end of program: ")?;
+                self.write_registers(out)
+            }
+            Stop::NeedsInput => writeln!(out, "instruction {} needs input", counter),
+            Stop::Invalid => writeln!(
+                out,
+                "instruction {} divides or takes a modulo by zero, overflows, or writes to a literal",
+                counter
+            ),
+        }
+    }
+
+    /// Evaluates the next instruction, printing it if `verbose` is set. Stops at a
+    /// breakpoint on the next instruction instead, unless resuming from that breakpoint.
+    fn step(
+        &mut self,
+        out: &mut impl Write,
+        verbose: bool,
+        resuming: bool,
+    ) -> io::Result<Result<(), Stop>> {
+        let index = self.monad.counter();
+        if !resuming && self.breakpoints.contains(&index) {
+            self.at_breakpoint = true;
+            return Ok(Err(Stop::Breakpoint));
+        }
+        let instruction = match self.monad.next_instruction() {
+            Some(instruction) => instruction,
+            None => return Ok(Err(Stop::End)),
+        };
+        let input = &mut self.input;
+        if self
+            .monad
+            .step(&mut std::iter::from_fn(|| input.pop_front()))
+            .is_none()
+        {
+            return Ok(Err(match instruction {
+                Instruction::Inp(_) if self.input.is_empty() => Stop::NeedsInput,
+                _ => Stop::Invalid,
+            }));
+        }
+        if verbose {
+            self.write_step(out, index, instruction)?;
+        }
+        Ok(Ok(()))
+    }
+
+    /// Evaluates up to `count` instructions, or every instruction if there is no count,
+    /// stopping early wherever the program stops.
+    fn run(&mut self, out: &mut impl Write, count: Option<usize>) -> io::Result<()> {
+        let verbose = count.is_some() || self.trace;
+        let resuming = std::mem::take(&mut self.at_breakpoint);
+        for i in 0..count.unwrap_or(usize::MAX) {
+            if let Err(stop) = self.step(out, verbose, resuming && i == 0)? {
+                return self.write_stop(out, stop);
+            }
+        }
+        Ok(())
+    }
+
+    fn list(&self, out: &mut impl Write, around: usize) -> io::Result<()> {
+        let start = around.saturating_sub(5);
+        let end = (around + 6).min(self.instructions.len());
+        for (index, instruction) in self.instructions.iter().enumerate().take(end).skip(start) {
+            let current = if index == self.monad.counter() {
+                '>'
+            } else {
+                ' '
+            };
+            let breakpoint = if self.breakpoints.contains(&index) {
+                '*'
+            } else {
+                ' '
+            };
+            writeln!(
+                out,
+                "{}{}{:>4}  {}",
+                current, breakpoint, index, instruction
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Carries out a command, returning `false` if the debugger should quit.
+    fn execute(&mut self, command: &str, out: &mut impl Write) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let index = || args.first().and_then(|arg| arg.parse::<usize>().ok());
+
+        match (name, args.len()) {
+            ("s" | "step", _) => self.run(out, Some(index().unwrap_or(1)))?,
+            ("c" | "continue", 0) => self.run(out, None)?,
+            ("b" | "break", 1) if index().is_some() => {
+                self.breakpoints.insert(index().unwrap());
+            }
+            ("d" | "delete", 1) if index().is_some() => {
+                if !self.breakpoints.remove(&index().unwrap()) {
+                    writeln!(out, "no breakpoint at instruction {}", args[0])?;
+                }
+            }
+            ("i" | "input", n) if n > 0 => match parse_input(&args) {
+                Some(input) => self.input.extend(input),
+                None => writeln!(out, "input must be digits or whitespace-separated numbers")?,
+            },
+            ("r" | "registers", 0) => self.write_registers(out)?,
+            ("l" | "list", _) => self.list(out, index().unwrap_or(self.monad.counter()))?,
+            ("t" | "trace", 0) => {
+                self.trace = !self.trace;
+                writeln!(out, "trace {}", if self.trace { "on" } else { "off" })?;
+            }
+            ("reset", 0) => {
+                self.monad = Monad::new(self.instructions);
+                self.input.clear();
+                self.at_breakpoint = false;
+            }
+            ("q" | "quit", 0) => return Ok(false),
+            _ => writeln!(out, "unknown command: {}", command.trim())?,
+        }
+        Ok(true)
+    }
+}
+
+/// Parses a single argument as one digit per character, or several arguments as one
+/// number each.
+fn parse_input(args: &[&str]) -> Option<Vec<i64>> {
+    match args {
+        [digits] => digits
+            .chars()
+            .map(|c| c.to_digit(10).map(i64::from))
+            .collect(),
+        numbers => numbers.iter().map(|number| number.parse().ok()).collect(),
+    }
+}
+
+fn load(path: &str) -> Result<Vec<Instruction>, String> {
+    let program = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    program
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Instruction::parse(&program, line).map_err(|e| e.to_string()))
+        .collect()
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (path, input) = match args.as_slice() {
+        [path] => (path, None),
+        [path, input] => (path, Some(input)),
+        _ => {
+            eprintln!("usage: alu FILE [DIGITS]");
+            process::exit(2);
+        }
+    };
+    let instructions = load(path).unwrap_or_else(|e| {
+        eprintln!("alu: {}: {}", path, e);
+        process::exit(1);
+    });
+
+    let mut debugger = Debugger::new(&instructions);
+    if let Some(input) = input {
+        match parse_input(&[input]) {
+            Some(input) => debugger.input.extend(input),
+            None => {
+                eprintln!("alu: input must be digits");
+                process::exit(2);
+            }
+        }
+    }
+    println!("loaded {} instructions", instructions.len());
+
+    let interactive = io::stdin().is_terminal();
+    let mut lines = io::stdin().lock().lines();
+    let mut stdout = io::stdout();
+    let mut last_command = String::new();
+    loop {
+        if interactive {
+            print!("(alu) ");
+            stdout.flush().unwrap();
+        }
+        let line = match lines.next() {
+            Some(line) => line.unwrap(),
+            None => break,
+        };
+        if !line.trim().is_empty() {
+            last_command = line;
+        }
+        if !debugger.execute(&last_command, &mut stdout).unwrap() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn session(program: &str, commands: &[&str]) -> String {
+        let instructions: Vec<Instruction> =
+            program.lines().map(|line| line.parse().unwrap()).collect();
+        let mut debugger = Debugger::new(&instructions);
+        let mut out = Vec::new();
+        for command in commands {
+            debugger.execute(command, &mut out).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    const PROGRAM: &str = "inp w\nadd z w\nmul z 3\ninp x\nadd z x\neql x 4";
+
+    #[test]
+    fn test_step() {
+        assert_eq!(
+            session(PROGRAM, &["input 2", "step 2", "s"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "    0  inp w         w=2 x=0 y=0 z=0",
+                "    1  add z w       w=2 x=0 y=0 z=2",
+                "    2  mul z 3       w=2 x=0 y=0 z=6",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_continue() {
+        assert_eq!(
+            session(PROGRAM, &["b 2", "i 24", "c", "c", "r"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "breakpoint at instruction 2",
+                "end of program: w=2 x=1 y=0 z=10",
+                "w=2 x=1 y=0 z=10",
+            ],
+        );
+        assert_eq!(
+            session(PROGRAM, &["i 2", "c", "input 1 -3", "c"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "instruction 3 needs input",
+                "end of program: w=2 x=0 y=0 z=7",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        assert_eq!(
+            session(PROGRAM, &["break 0", "i 24", "c", "c", "c"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "breakpoint at instruction 0",
+                "end of program: w=2 x=1 y=0 z=10",
+                "end of program: w=2 x=1 y=0 z=10",
+            ],
+        );
+        assert_eq!(
+            session(PROGRAM, &["b 1", "b 2", "i 2", "c", "c", "step 2"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "breakpoint at instruction 1",
+                "breakpoint at instruction 2",
+                "    2  mul z 3       w=2 x=0 y=0 z=6",
+                "instruction 3 needs input",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_invalid_instructions() {
+        for program in [
+            "inp w\nmod w 0",
+            "inp w\ndiv w 0",
+            "inp w\nmul w 9223372036854775807",
+        ] {
+            assert_eq!(
+                session(program, &["i 9", "c", "r"])
+                    .lines()
+                    .collect::<Vec<_>>(),
+                [
+                    "instruction 1 divides or takes a modulo by zero, overflows, or writes to a literal",
+                    "w=9 x=0 y=0 z=0",
+                ],
+                "{}",
+                program,
+            );
+        }
+    }
+
+    #[test]
+    fn test_list() {
+        assert_eq!(
+            session(PROGRAM, &["break 4", "i 2", "step", "list 1"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "    0  inp w         w=2 x=0 y=0 z=0",
+                "     0  inp w",
+                ">    1  add z w",
+                "     2  mul z 3",
+                "     3  inp x",
+                " *   4  add z x",
+                "     5  eql x 4",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unknown_commands() {
+        assert_eq!(
+            session(PROGRAM, &["break", "input 1x", "jump 3"])
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "unknown command: break",
+                "input must be digits or whitespace-separated numbers",
+                "unknown command: jump 3",
+            ],
+        );
+    }
+}
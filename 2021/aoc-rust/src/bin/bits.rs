@@ -0,0 +1,53 @@
+//! Decodes a day 16 BITS transmission and prints its packet tree.
+//!
+//! Usage: `bits [--dot] [FILE]`
+//!
+//! Reads the hexadecimal transmission from FILE, or from stdin if no file is given, and
+//! prints the packet tree as an indented S-expression, or as a Graphviz digraph with `--dot`.
+//! The files in `misc/` are generated this way from the puzzle input:
+//!
+//! ```text
+//! cargo run --bin bits -- input/2021/day16.txt > misc/day16_ast.txt
+//! cargo run --bin bits -- --dot input/2021/day16.txt > misc/day16_ast.dot
+//! ```
+
+use aoc_rust_2021::day16::Packet;
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
+};
+
+fn main() {
+    let mut dot = false;
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--dot" => dot = true,
+            _ => path = Some(arg),
+        }
+    }
+
+    let transmission = match path {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut transmission = String::new();
+            io::stdin()
+                .read_to_string(&mut transmission)
+                .map(|_| transmission)
+        }
+    };
+    let transmission = transmission.unwrap_or_else(|e| {
+        eprintln!("bits: {}", e);
+        process::exit(1);
+    });
+
+    match Packet::from_hex(&transmission) {
+        Ok(packet) if dot => print!("{}", packet.to_dot()),
+        Ok(packet) => println!("{:#}", packet),
+        Err(e) => {
+            eprintln!("bits: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Aligns the day 19 scanners and reports how each one was placed.
+//!
+//! Usage: `scanners [--dot] [--overlap N] [FILE]`
+//!
+//! Reads the scanner reports from FILE, or from stdin if no file is given, and prints where
+//! each scanner is, which scanner it was aligned against, and which scanners could not be
+//! aligned at all. With `--dot`, prints the graph of alignments as a Graphviz digraph
+//! instead. `--overlap` sets how many beacons two scanners must share to be aligned, which
+//! is 12 in the puzzle. Exits with status 1 if any scanner could not be aligned.
+//!
+//! ```text
+//! cargo run --bin scanners -- --dot input/2021/day19.txt | dot -Tsvg > scanners.svg
+//! ```
+
+use aoc_rust_2021::day19::{merge_all_perspectives, parse};
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
+};
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("scanners: {}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let mut dot = false;
+    let mut overlap = 12;
+    let mut path = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dot" => dot = true,
+            "--overlap" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => overlap = n,
+                None => fail("--overlap needs a count of beacons"),
+            },
+            _ => path = Some(arg),
+        }
+    }
+
+    let report = match path {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut report = String::new();
+            io::stdin().read_to_string(&mut report).map(|_| report)
+        }
+    };
+    let report = report.unwrap_or_else(|e| fail(e));
+    let scanners = parse(report.trim_end()).unwrap_or_else(|e| fail(e));
+
+    let map = merge_all_perspectives(&scanners, overlap);
+    if dot {
+        print!("{}", map.to_dot());
+    } else {
+        print!("{}", map.report());
+    }
+    if !map.unaligned().is_empty() {
+        process::exit(1);
+    }
+}
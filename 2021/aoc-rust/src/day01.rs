@@ -1,3 +1,4 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 
 type Input = Vec<i64>;
@@ -8,19 +9,19 @@ type Output = usize;
 // ========================================
 
 #[aoc_generator(day1, part1, Original)]
-fn input_generator_part1(raw_input: &str) -> Input {
+fn input_generator_part1(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .map(|line| line.parse().unwrap())
+        .map(|line| parse::value(raw_input, line, "a depth"))
         .collect()
 }
 
 #[aoc_generator(day1, part2, Original)]
-fn input_generator_part2(raw_input: &str) -> Input {
-    input_generator_part1(raw_input)
+fn input_generator_part2(raw_input: &str) -> Result<Input, ParseError> {
+    Ok(input_generator_part1(raw_input)?
         .windows(3)
         .map(|window| window.iter().sum())
-        .collect()
+        .collect())
 }
 
 #[aoc(day1, part1, Original)]
@@ -46,7 +47,7 @@ fn solve_part2(input: &Input) -> Output {
 // last number.
 
 #[aoc_generator(day1, Clever)]
-fn input_generator_clever(raw_input: &str) -> Input {
+fn input_generator_clever(raw_input: &str) -> Result<Input, ParseError> {
     input_generator_part1(raw_input)
 }
 
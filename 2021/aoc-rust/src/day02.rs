@@ -1,5 +1,5 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
-use std::str::FromStr;
 
 const UP: &str = "up ";
 const DOWN: &str = "down ";
@@ -15,25 +15,20 @@ enum Instruction {
     Forward(i64),
 }
 
-impl FromStr for Instruction {
-    type Err = <i64 as FromStr>::Err;
-    fn from_str(value: &str) -> Result<Self, Self::Err> {
-        value
-            .trim_start_matches(UP)
-            .parse()
-            .map(Instruction::Up)
-            .or_else(|_| {
-                value
-                    .trim_start_matches(DOWN)
-                    .parse()
-                    .map(Instruction::Down)
-            })
-            .or_else(|_| {
-                value
-                    .trim_start_matches(FORWARD)
-                    .parse()
-                    .map(Instruction::Forward)
-            })
+impl Instruction {
+    /// Parses a line of the puzzle input, such as `forward 5`.
+    fn parse(raw_input: &str, line: &str) -> Result<Self, ParseError> {
+        let (amount, instruction): (_, fn(i64) -> Self) =
+            if let Some(amount) = line.strip_prefix(UP) {
+                (amount, Instruction::Up)
+            } else if let Some(amount) = line.strip_prefix(DOWN) {
+                (amount, Instruction::Down)
+            } else if let Some(amount) = line.strip_prefix(FORWARD) {
+                (amount, Instruction::Forward)
+            } else {
+                return Err(ParseError::new(raw_input, line, "up, down, or forward"));
+            };
+        parse::value(raw_input, amount, "a distance").map(instruction)
     }
 }
 
@@ -78,10 +73,10 @@ impl Submarine {
 }
 
 #[aoc_generator(day2)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .map(|line| line.parse().unwrap())
+        .map(|line| Instruction::parse(raw_input, line))
         .collect()
 }
 
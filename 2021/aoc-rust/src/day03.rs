@@ -1,3 +1,4 @@
+use aoc_common::parse::ParseError;
 use aoc_runner_derive::*;
 use flow_control::break_if;
 
@@ -5,10 +6,13 @@ type Input = Vec<i64>;
 type Output = i64;
 
 #[aoc_generator(day3)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .map(|line| i64::from_str_radix(line, 2).unwrap())
+        .map(|line| {
+            i64::from_str_radix(line, 2)
+                .map_err(|_| ParseError::new(raw_input, line, "a binary number"))
+        })
         .collect()
 }
 
@@ -1,3 +1,4 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use itertools::Itertools;
 
@@ -63,31 +64,40 @@ impl BingoBoard {
     }
 }
 
+fn parse_row(raw_input: &str, line: &str) -> Result<Vec<Square>, ParseError> {
+    let row = line
+        .split_whitespace()
+        .map(|n| parse::value::<i64>(raw_input, n, "a number").map(Square::from))
+        .collect::<Result<Vec<_>, _>>()?;
+    if row.len() != 5 {
+        return Err(ParseError::new(raw_input, line, "a row of five numbers"));
+    }
+    Ok(row)
+}
+
 #[aoc_generator(day4)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     let mut lines = raw_input.lines().filter(|line| !line.trim().is_empty());
     let calls = lines
         .next()
-        .unwrap()
+        .ok_or_else(|| ParseError::end_of_input(raw_input, "the numbers to call"))?
         .split(',')
-        .map(|n| n.parse().unwrap())
-        .collect();
+        .map(|n| parse::value(raw_input, n, "a number"))
+        .collect::<Result<_, _>>()?;
     let boards = lines
         .chunks(5)
         .into_iter()
         .map(|chunk| {
-            chunk
-                .into_iter()
-                .map(|line| {
-                    line.split_whitespace()
-                        .map(|n| n.parse::<i64>().unwrap().into())
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-                .into()
+            let rows = chunk
+                .map(|line| parse_row(raw_input, line))
+                .collect::<Result<Vec<_>, _>>()?;
+            if rows.len() != 5 {
+                return Err(ParseError::end_of_input(raw_input, "a row of five numbers"));
+            }
+            Ok(rows.into())
         })
-        .collect();
-    (calls, boards)
+        .collect::<Result<_, _>>()?;
+    Ok((calls, boards))
 }
 
 fn play_bingo((calls, boards): &Input) -> Vec<(i64, BingoBoard)> {
@@ -96,7 +106,7 @@ fn play_bingo((calls, boards): &Input) -> Vec<(i64, BingoBoard)> {
     calls.iter().for_each(|&n| {
         winners.extend(
             boards
-                .drain_filter(|board| board.marked_with(n).is_winner())
+                .extract_if(.., |board| board.marked_with(n).is_winner())
                 .map(|board| (n, board)),
         );
     });
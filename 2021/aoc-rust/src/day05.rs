@@ -1,8 +1,11 @@
+use aoc_common::{
+    num::Approach,
+    parse::{self, ParseError},
+};
 use aoc_runner_derive::*;
 
 use itertools::Itertools;
-use std::{cmp::Ordering, iter};
-use text_io::scan;
+use std::iter;
 
 type Input = Vec<Line>;
 type Output = usize;
@@ -21,31 +24,17 @@ impl From<[i16; 4]> for Line {
 }
 
 #[aoc_generator(day5)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
         .map(|line| {
-            let [x1, y1, x2, y2]: [i16; 4];
-            scan!(line.bytes() => "{},{} -> {},{}", x1, y1, x2, y2);
-            [x1, y1, x2, y2].into()
+            let [x1, y1, x2, y2] = parse::scan(raw_input, line, "{},{} -> {},{}")?
+                .map(|n| parse::value(raw_input, n, "a coordinate"));
+            Ok([x1?, y1?, x2?, y2?].into())
         })
         .collect()
 }
 
-trait Approach {
-    fn approach(self, target: Self) -> Self;
-}
-
-impl Approach for i16 {
-    fn approach(self, target: Self) -> Self {
-        match target.cmp(&self) {
-            Ordering::Less => self - 1,
-            Ordering::Equal => self,
-            Ordering::Greater => self + 1,
-        }
-    }
-}
-
 impl Line {
     fn is_not_diagonal(&self) -> bool {
         self.x1 == self.x2 || self.y1 == self.y2
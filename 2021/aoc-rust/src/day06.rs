@@ -1,17 +1,18 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 
 type Input = [u64; 9];
 type Output = u64;
 
 #[aoc_generator(day6)]
-fn input_generator_arrays(raw_input: &str) -> Input {
-    raw_input
-        .split(',')
-        .map(|n| n.parse::<usize>().unwrap())
-        .fold([0; 9], |mut arr, timer| {
-            arr[timer] += 1;
-            arr
-        })
+fn input_generator_arrays(raw_input: &str) -> Result<Input, ParseError> {
+    const EXPECTED: &str = "a timer from 0 to 8";
+    raw_input.split(',').try_fold([0; 9], |mut arr, n| {
+        let timer: usize = parse::value(raw_input, n, EXPECTED)?;
+        *arr.get_mut(timer)
+            .ok_or_else(|| ParseError::new(raw_input, n, EXPECTED))? += 1;
+        Ok(arr)
+    })
 }
 
 fn spawn_count(fish: [u64; 9], days_left: u64) -> u64 {
@@ -1,3 +1,4 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use itertools::Itertools;
 
@@ -5,12 +6,12 @@ type Input = Vec<i64>;
 type Output = i64;
 
 #[aoc_generator(day7)]
-fn input_generator_arrays(raw_input: &str) -> Input {
-    raw_input
+fn input_generator_arrays(raw_input: &str) -> Result<Input, ParseError> {
+    let positions: Result<Input, _> = raw_input
         .split(',')
-        .map(|n| n.parse().unwrap())
-        .sorted()
-        .collect()
+        .map(|n| parse::value(raw_input, n, "a position"))
+        .collect();
+    positions.map(|positions| positions.into_iter().sorted().collect())
 }
 
 #[derive(Clone, Copy)]
@@ -1,3 +1,4 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use flow_control::return_if;
 
@@ -53,53 +54,56 @@ const DIGIT9: u32 = A + B + C + D + F + G;
 // indices for each segment that is present in the observed output sequence.
 //
 // This will produce one of the DIGIT0, DIGIT1, DIGIT2... constants above.
-fn parse_segment_map<'a>(mut iter: impl Iterator<Item = &'a str>) -> [u32; 7] {
+fn parse_segment_map(patterns: &str) -> [u32; 7] {
     let mut segment_map = [1; 7];
-    iter.next()
-        .unwrap()
-        .split_whitespace()
-        .for_each(|sequence| {
-            sequence.bytes().for_each(|byte| {
-                segment_map[(byte - b'a') as usize] *= sequence.len() as u32;
-            });
+    patterns.split_whitespace().for_each(|sequence| {
+        sequence.bytes().for_each(|byte| {
+            segment_map[(byte - b'a') as usize] *= sequence.len() as u32;
         });
+    });
     segment_map
 }
 
 // Once we have a weighted sum that maps to one of the above DIGIT0, DIGIT1... constants,
 // we can map that directly to its integer value.
-fn to_digit(weighted_segment_sum: u32) -> u8 {
-    return_if!(weighted_segment_sum == DIGIT0, 0);
-    return_if!(weighted_segment_sum == DIGIT1, 1);
-    return_if!(weighted_segment_sum == DIGIT2, 2);
-    return_if!(weighted_segment_sum == DIGIT3, 3);
-    return_if!(weighted_segment_sum == DIGIT4, 4);
-    return_if!(weighted_segment_sum == DIGIT5, 5);
-    return_if!(weighted_segment_sum == DIGIT6, 6);
-    return_if!(weighted_segment_sum == DIGIT7, 7);
-    return_if!(weighted_segment_sum == DIGIT8, 8);
-    return_if!(weighted_segment_sum == DIGIT9, 9);
-    unreachable!("invalid segment sum");
+fn to_digit(weighted_segment_sum: u32) -> Option<u8> {
+    return_if!(weighted_segment_sum == DIGIT0, Some(0));
+    return_if!(weighted_segment_sum == DIGIT1, Some(1));
+    return_if!(weighted_segment_sum == DIGIT2, Some(2));
+    return_if!(weighted_segment_sum == DIGIT3, Some(3));
+    return_if!(weighted_segment_sum == DIGIT4, Some(4));
+    return_if!(weighted_segment_sum == DIGIT5, Some(5));
+    return_if!(weighted_segment_sum == DIGIT6, Some(6));
+    return_if!(weighted_segment_sum == DIGIT7, Some(7));
+    return_if!(weighted_segment_sum == DIGIT8, Some(8));
+    return_if!(weighted_segment_sum == DIGIT9, Some(9));
+    None
 }
 
 #[aoc_generator(day8)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
         .map(|line| {
-            let mut split = line.split(" | ");
-            let segment_map = parse_segment_map(&mut split);
-            split
-                .next()
-                .unwrap()
+            if let Some(position) = line.find(|c: char| !matches!(c, 'a'..='g' | ' ' | '|')) {
+                return Err(ParseError::new(
+                    raw_input,
+                    &line[position..],
+                    "a segment from a to g",
+                ));
+            }
+            let [patterns, output] = parse::scan(raw_input, line, "{} | {}")?;
+            let segment_map = parse_segment_map(patterns);
+            output
                 .split_whitespace()
-                .map(move |sequence| {
-                    sequence
+                .map(|sequence| {
+                    let weighted_segment_sum = sequence
                         .bytes()
                         .map(|byte| segment_map[(byte - b'a') as usize])
-                        .sum::<u32>()
+                        .sum();
+                    to_digit(weighted_segment_sum)
+                        .ok_or_else(|| ParseError::new(raw_input, sequence, "a wired digit"))
                 })
-                .map(to_digit)
                 .collect()
         })
         .collect()
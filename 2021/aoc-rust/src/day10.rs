@@ -1,3 +1,4 @@
+use aoc_common::parse::ParseError;
 use aoc_runner_derive::*;
 use flow_control::return_if;
 use itertools::Itertools;
@@ -6,10 +7,17 @@ type Input = Vec<Vec<u8>>;
 type Output = usize;
 
 #[aoc_generator(day10)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .map(|line| line.bytes().collect())
+        .map(|line| match line.find(|c| !"()[]{}<>".contains(c)) {
+            Some(position) => Err(ParseError::new(
+                raw_input,
+                &line[position..],
+                "one of ()[]{}<>",
+            )),
+            None => Ok(line.bytes().collect()),
+        })
         .collect()
 }
 
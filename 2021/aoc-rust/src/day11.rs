@@ -1,35 +1,26 @@
+use aoc_common::{grid::Grid, parse::ParseError};
 use aoc_runner_derive::*;
 use std::iter::repeat_with;
 
-type Input = Vec<Vec<u8>>;
+type Input = Grid<u8>;
 type Output = usize;
 
 #[aoc_generator(day11)]
-fn input_generator(raw_input: &str) -> Input {
-    raw_input
-        .lines()
-        .map(|line| line.bytes().map(|byte| byte - b'0').collect())
-        .collect()
-}
-
-fn grid_points_iter(
-    (start_row, start_col): (usize, usize),
-    rows: usize,
-    cols: usize,
-) -> impl Iterator<Item = (usize, usize)> {
-    (0..rows).flat_map(move |row| (0..cols).map(move |col| (start_row + row, start_col + col)))
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    Grid::try_parse_from_str(raw_input, "a digit", |byte| {
+        byte.is_ascii_digit().then(|| byte - b'0')
+    })
 }
 
 fn flash(grid: &mut Input) -> usize {
-    grid_points_iter((0, 0), grid.len(), grid[0].len())
-        .flat_map(|(row, col)| {
-            (grid[row][col] > 9).then(|| {
-                grid_points_iter((row - 1, col - 1), 3, 3).for_each(|(row, col)| {
-                    if let Some(n) = grid.get_mut(row).and_then(|row| row.get_mut(col)) {
-                        (*n != 0).then(|| *n += 1);
-                    }
+    grid.points()
+        .flat_map(|point| {
+            (grid[point] > 9).then(|| {
+                grid.surrounding_neighbors(point).for_each(|neighbor| {
+                    let n = &mut grid[neighbor];
+                    (*n != 0).then(|| *n += 1);
                 });
-                grid[row][col] = 0;
+                grid[point] = 0;
                 1
             })
         })
@@ -37,7 +28,7 @@ fn flash(grid: &mut Input) -> usize {
 }
 
 fn step(grid: &mut Input) -> usize {
-    grid_points_iter((0, 0), grid.len(), grid[0].len()).for_each(|(row, col)| grid[row][col] += 1);
+    grid.points().for_each(|point| grid[point] += 1);
     repeat_with(|| flash(grid))
         .take_while(|&flashes| flashes > 0)
         .sum()
@@ -54,7 +45,7 @@ fn solve_part2(input: &Input) -> Output {
     let mut grid = input.clone();
     (1..)
         .map(|n| (n, step(&mut grid)))
-        .find(|&(_, flashes)| flashes == (input.len() * input[0].len()))
+        .find(|&(_, flashes)| flashes == (input.rows() * input.cols()))
         .unwrap()
         .0
 }
@@ -1,7 +1,7 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use flow_control::return_if;
 use std::collections::BTreeMap;
-use text_io::scan;
 
 type Input = BTreeMap<Cave, Vec<Cave>>;
 type Output = usize;
@@ -86,19 +86,26 @@ fn get_count(
     count
 }
 
+fn parse_cave(raw_input: &str, name: &str) -> Result<Cave, ParseError> {
+    if !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ParseError::new(raw_input, name, "a cave name"));
+    }
+    Ok(Cave::from(name.to_string()))
+}
+
 #[aoc_generator(day12)]
-fn input_generator(raw_input: &str) -> Input {
-    raw_input.lines().fold(BTreeMap::new(), |mut map, line| {
-        let [key, value]: [String; 2];
-        scan!(line.bytes() => "{}-{}", key, value);
-        let (key, value): (Cave, Cave) = (key.into(), value.into());
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    raw_input.lines().try_fold(Input::new(), |mut map, line| {
+        let [key, value] = parse::scan(raw_input, line, "{}-{}")?;
+        let key = parse_cave(raw_input, key)?;
+        let value = parse_cave(raw_input, value)?;
         if !matches!(key, Cave::Start) && !matches!(value, Cave::End) {
             map.entry(key).or_default().push(value);
         }
         if !matches!(value, Cave::Start) && !matches!(key, Cave::End) {
             map.entry(value).or_default().push(key);
         }
-        map
+        Ok(map)
     })
 }
 
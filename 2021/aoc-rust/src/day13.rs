@@ -1,6 +1,6 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use std::cmp::Ordering;
-use text_io::scan;
 
 type Dots = Vec<(i16, i16)>;
 type Input = (Dots, Vec<Fold>);
@@ -13,34 +13,31 @@ enum Fold {
 }
 
 #[aoc_generator(day13)]
-fn input_generator(raw_input: &str) -> Input {
-    let mut iter = raw_input.split("\n\n");
-    let dots = iter
-        .next()
-        .unwrap()
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    let (raw_dots, raw_folds) = raw_input
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::end_of_input(raw_input, "a blank line before the folds"))?;
+    let dots = raw_dots
         .lines()
         .map(|line| {
-            let [x, y]: [i16; 2];
-            scan!(line.bytes() => "{},{}", x, y);
-            (x, y)
+            let [x, y] = parse::scan(raw_input, line, "{},{}")?
+                .map(|n| parse::value(raw_input, n, "a coordinate"));
+            Ok((x?, y?))
         })
-        .collect();
-    let folds = iter
-        .next()
-        .unwrap()
+        .collect::<Result<_, _>>()?;
+    let folds = raw_folds
         .lines()
         .map(|line| {
-            let direction: char;
-            let location: i16;
-            scan!(line.bytes() => "fold along {}={}", direction, location);
-            if direction == 'y' {
-                Fold::Up(location)
-            } else {
-                Fold::Left(location)
+            let [direction, location] = parse::scan(raw_input, line, "fold along {}={}")?;
+            let location = parse::value(raw_input, location, "a fold line")?;
+            match direction {
+                "y" => Ok(Fold::Up(location)),
+                "x" => Ok(Fold::Left(location)),
+                _ => Err(ParseError::new(raw_input, direction, "x or y")),
             }
         })
-        .collect();
-    (dots, folds)
+        .collect::<Result<_, _>>()?;
+    Ok((dots, folds))
 }
 
 impl Fold {
@@ -1,3 +1,4 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use flow_control::{continue_if, return_if};
 
@@ -23,33 +24,40 @@ const fn pair_from(index: usize) -> (u16, u16) {
     )
 }
 
+/// Parses `text`, which must be a slice of `raw_map`, as a sequence of uppercase elements.
+fn elements(raw_map: &str, text: &str, expected: &str) -> Result<Vec<u16>, ParseError> {
+    match text.find(|c: char| !c.is_ascii_uppercase()) {
+        Some(position) => Err(ParseError::new(raw_map, &text[position..], expected)),
+        None => Ok(text.bytes().map(|byte| byte as u16).collect()),
+    }
+}
+
 #[aoc_generator(day14, part1, nordzilla)]
 #[aoc_generator(day14, part2, nordzilla)]
-fn input_generator(raw_map: &str) -> Input {
+fn input_generator(raw_map: &str) -> Result<Input, ParseError> {
     let mut counts = [0; MAX_INDEX + 1];
     let mut pairs = [(0, None); MAX_PAIR_INDEX + 1];
-    let mut iter = raw_map.split("\n\n");
-    let seed = iter
-        .next()
-        .unwrap()
-        .bytes()
-        .map(|byte| byte as u16)
-        .collect::<Vec<_>>();
+    let (raw_seed, raw_rules) = raw_map
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::end_of_input(raw_map, "a blank line before the rules"))?;
+    let seed = elements(raw_map, raw_seed, "a polymer template")?;
     for &byte in &seed {
         counts[single_index(byte)] += 1;
     }
     for w in seed.windows(2) {
         pairs[combined_index(w[0], w[1])].0 += 1;
     }
-    iter.next().unwrap().lines().for_each(|line| {
-        let mut iter = line.split(" -> ");
-        let pair = iter.next().unwrap();
-        let c1 = pair.as_bytes().get(0).copied().unwrap() as u16;
-        let c2 = pair.as_bytes().get(1).copied().unwrap() as u16;
-        let c3 = iter.next().unwrap().chars().next().unwrap() as u16;
-        pairs[combined_index(c1, c2)].1 = Some(c3);
-    });
-    (counts, pairs)
+    for line in raw_rules.lines() {
+        let [raw_pair, raw_insert] = parse::scan(raw_map, line, "{} -> {}")?;
+        let pair = elements(raw_map, raw_pair, "a pair of elements")?;
+        let insert = elements(raw_map, raw_insert, "an element")?;
+        match (pair.as_slice(), insert.as_slice()) {
+            (&[c1, c2], &[c3]) => pairs[combined_index(c1, c2)].1 = Some(c3),
+            (&[_, _], _) => return Err(ParseError::new(raw_map, raw_insert, "an element")),
+            _ => return Err(ParseError::new(raw_map, raw_pair, "a pair of elements")),
+        }
+    }
+    Ok((counts, pairs))
 }
 
 fn step(counts: &mut CountsMap, in_pairs: &PairsMap) -> PairsMap {
@@ -1,7 +1,21 @@
+use aoc_common::{
+    combinator::{self, *},
+    num::from_bits,
+    parse,
+};
 use aoc_runner_derive::*;
-use flow_control::return_if;
+use num::{BigUint, Zero};
 
-type Input = Vec<u8>;
+mod compiler;
+mod encoder;
+mod eval;
+mod render;
+
+pub use compiler::compile;
+pub use encoder::{EncodeError, LengthType};
+pub use eval::{Overflow, Value};
+
+type Input = Packet;
 type Output = usize;
 
 const BINARY: [[u8; 4]; 16] = [
@@ -23,51 +37,52 @@ const BINARY: [[u8; 4]; 16] = [
     [1, 1, 1, 1],
 ];
 
-fn number_from(bits: &[u8]) -> usize {
-    bits.iter()
-        .rev()
-        .zip(0..)
-        .fold(0, |n, (&bit, place_value)| {
-            n | (bit as usize) << place_value
-        })
-}
-
-fn hex_to_binary(byte: u8) -> [u8; 4] {
+fn hex_to_binary(byte: u8) -> Option<[u8; 4]> {
     match byte {
-        b'0' => BINARY[00],
-        b'1' => BINARY[01],
-        b'2' => BINARY[02],
-        b'3' => BINARY[03],
-        b'4' => BINARY[04],
-        b'5' => BINARY[05],
-        b'6' => BINARY[06],
-        b'7' => BINARY[07],
-        b'8' => BINARY[08],
-        b'9' => BINARY[09],
-        b'A' => BINARY[10],
-        b'B' => BINARY[11],
-        b'C' => BINARY[12],
-        b'D' => BINARY[13],
-        b'E' => BINARY[14],
-        b'F' => BINARY[15],
-        _ => unreachable!(),
+        b'0' => Some(BINARY[00]),
+        b'1' => Some(BINARY[01]),
+        b'2' => Some(BINARY[02]),
+        b'3' => Some(BINARY[03]),
+        b'4' => Some(BINARY[04]),
+        b'5' => Some(BINARY[05]),
+        b'6' => Some(BINARY[06]),
+        b'7' => Some(BINARY[07]),
+        b'8' => Some(BINARY[08]),
+        b'9' => Some(BINARY[09]),
+        b'A' => Some(BINARY[10]),
+        b'B' => Some(BINARY[11]),
+        b'C' => Some(BINARY[12]),
+        b'D' => Some(BINARY[13]),
+        b'E' => Some(BINARY[14]),
+        b'F' => Some(BINARY[15]),
+        _ => None,
     }
 }
 
 #[aoc_generator(day16, part1, nordzilla)]
 #[aoc_generator(day16, part2, nordzilla)]
-fn input_generator(raw_input: &str) -> Input {
-    raw_input
-        .bytes()
-        .flat_map(|byte| hex_to_binary(byte))
-        .collect()
+fn input_generator(raw_input: &str) -> Result<Input, parse::ParseError> {
+    Packet::from_hex(raw_input)
+}
+
+/// Expands `hex`, which must be a slice of `input`, into one byte per bit.
+fn hex_bits(input: &str, hex: &str) -> Result<Vec<u8>, parse::ParseError> {
+    hex.char_indices()
+        .map(|(index, c)| {
+            u8::try_from(c)
+                .ok()
+                .and_then(hex_to_binary)
+                .ok_or_else(|| parse::ParseError::new(input, &hex[index..], "a hexadecimal digit"))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|digits| digits.concat())
 }
 
 // =====================================================================================
 // Types
 // =====================================================================================
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PacketType {
     Sum,
     Product,
@@ -87,6 +102,13 @@ impl PacketType {
     fn is_operator(self) -> bool {
         !self.is_literal()
     }
+
+    fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            PacketType::GreaterThan | PacketType::LessThan | PacketType::EqualTo
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -95,7 +117,7 @@ pub enum PacketLength {
     Packets(usize),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Header {
     version: usize,
     type_id: PacketType,
@@ -114,18 +136,18 @@ pub enum LiteralSegment {
 }
 
 impl LiteralSegment {
-    fn shift_left(self, place_value: usize) -> usize {
+    fn nibble(self) -> u8 {
         match self {
-            LiteralSegment::Part(n) => n << place_value,
-            LiteralSegment::End(n) => n << place_value,
+            LiteralSegment::Part(n) => n as u8,
+            LiteralSegment::End(n) => n as u8,
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct Packet {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
     header: Header,
-    value: usize,
+    value: BigUint,
     sub_packets: Vec<Packet>,
 }
 
@@ -134,25 +156,34 @@ impl Packet {
         Self {
             header,
             sub_packets: Vec::new(),
-            value: segments
-                .into_iter()
-                .rev()
-                .zip((0..).step_by(4))
-                .fold(0, |n, (segment, place_value)| {
-                    n | segment.shift_left(place_value)
-                }),
+            value: BigUint::from_radix_be(
+                &segments
+                    .into_iter()
+                    .map(LiteralSegment::nibble)
+                    .collect::<Vec<_>>(),
+                16,
+            )
+            .unwrap(),
+        }
+    }
+
+    fn literal_value(header: Header, value: BigUint) -> Self {
+        Self {
+            header,
+            value,
+            sub_packets: Vec::new(),
         }
     }
 
     fn operator(header: Header, sub_packets: Vec<Packet>) -> Self {
         Self {
             header,
-            value: 0,
+            value: BigUint::zero(),
             sub_packets,
         }
     }
 
-    fn version_sum(&self) -> usize {
+    pub fn version_sum(&self) -> usize {
         self.header.version
             + self
                 .sub_packets
@@ -160,118 +191,23 @@ impl Packet {
                 .map(Packet::version_sum)
                 .sum::<usize>()
     }
-
-    fn eval(&self) -> usize {
-        let mut sub_packet_values = self.sub_packets.iter().map(Packet::eval);
-        match self.header.type_id {
-            PacketType::Sum => sub_packet_values.sum(),
-            PacketType::Product => sub_packet_values.product(),
-            PacketType::Minimum => sub_packet_values.min().unwrap(),
-            PacketType::Maximum => sub_packet_values.max().unwrap(),
-            PacketType::Literal => self.value,
-            PacketType::GreaterThan => {
-                (sub_packet_values.next().unwrap() > sub_packet_values.next().unwrap()) as usize
-            }
-            PacketType::LessThan => {
-                (sub_packet_values.next().unwrap() < sub_packet_values.next().unwrap()) as usize
-            }
-            PacketType::EqualTo => {
-                (sub_packet_values.next().unwrap() == sub_packet_values.next().unwrap()) as usize
-            }
-        }
-    }
 }
 
 // =====================================================================================
-// Parser combinator primitives
+// Parsers for types
 // =====================================================================================
 
-type ParseOption<'a, T> = Option<(T, &'a [u8])>;
-
-trait MapFront<Front, Mapped> {
-    type Output;
-    fn map_front(self, f: impl Fn(Front) -> Mapped) -> Self::Output;
-}
-
-impl<Front, Mapped, Back> MapFront<Front, Mapped> for Option<(Front, Back)> {
-    type Output = Option<(Mapped, Back)>;
-    fn map_front(self, f: impl Fn(Front) -> Mapped) -> Self::Output {
-        self.map(|(front, back)| (f(front), back))
-    }
-}
-
-fn take(n: usize, bits: &[u8]) -> ParseOption<&[u8]> {
-    (n <= bits.len()).then(|| bits.split_at(n))
-}
-
-fn take_as_number(n: usize, bits: &[u8]) -> ParseOption<usize> {
-    take(n, bits).map_front(number_from)
-}
-
-fn require<T, PredicateFn, ParseFn>(
-    predicate: PredicateFn,
-    parse: ParseFn,
-    bits: &[u8],
-) -> ParseOption<T>
-where
-    PredicateFn: Fn(&T) -> bool,
-    ParseFn: Fn(&[u8]) -> ParseOption<T>,
-{
-    parse(bits).filter(|(parsed, _)| predicate(parsed))
-}
+type ParseResult<'a, T> = combinator::ParseResult<'a, u8, T>;
 
-fn parse_exactly<T, ParseFn>(n: usize, parse: ParseFn, mut bits: &[u8]) -> ParseOption<Vec<T>>
-where
-    ParseFn: Fn(&[u8]) -> ParseOption<T>,
-{
-    let mut values = Vec::new();
-    while let Some((parsed, tail)) = parse(bits) {
-        bits = tail;
-        values.push(parsed);
-        return_if!(values.len() == n, Some((values, bits)));
-    }
-    None
-}
-
-fn parse_one_or_more<T, ParseFn>(parse: ParseFn, mut bits: &[u8]) -> ParseOption<Vec<T>>
-where
-    ParseFn: Fn(&[u8]) -> ParseOption<T>,
-{
-    let mut values = Vec::new();
-    while let Some((parsed, tail)) = parse(bits) {
-        bits = tail;
-        values.push(parsed);
-    }
-    (!values.is_empty()).then(|| (values, bits))
+fn take_as_number(n: usize, bits: &[u8]) -> ParseResult<'_, usize> {
+    take(n, bits).map_front(from_bits)
 }
 
-fn parse_until<T, PredicateFn, ParseFn>(
-    predicate: PredicateFn,
-    parse: ParseFn,
-    mut bits: &[u8],
-) -> ParseOption<Vec<T>>
-where
-    PredicateFn: Fn(&T) -> bool,
-    ParseFn: Fn(&[u8]) -> ParseOption<T>,
-{
-    let mut values = Vec::new();
-    while let Some((parsed, tail)) = parse(bits) {
-        bits = tail;
-        values.push(parsed);
-        return_if!(predicate(values.last().unwrap()), Some((values, bits)));
-    }
-    None
-}
-
-// =====================================================================================
-// Parsers for types
-// =====================================================================================
-
-fn parse_version(bits: &[u8]) -> ParseOption<usize> {
+fn parse_version(bits: &[u8]) -> ParseResult<'_, usize> {
     take_as_number(3, bits)
 }
 
-fn parse_packet_type(bits: &[u8]) -> ParseOption<PacketType> {
+fn parse_packet_type(bits: &[u8]) -> ParseResult<'_, PacketType> {
     take_as_number(3, bits).map_front(|n| match n {
         0 => PacketType::Sum,
         1 => PacketType::Product,
@@ -281,38 +217,42 @@ fn parse_packet_type(bits: &[u8]) -> ParseOption<PacketType> {
         5 => PacketType::GreaterThan,
         6 => PacketType::LessThan,
         7 => PacketType::EqualTo,
-        n => panic!("Unsupported packet type! {}", n),
+        _ => unreachable!(),
     })
 }
 
-fn parse_header(bits: &[u8]) -> ParseOption<Header> {
+fn parse_header(bits: &[u8]) -> ParseResult<'_, Header> {
     parse_version(bits).and_then(|(version, bits)| {
         parse_packet_type(bits).map_front(|type_id| Header::new(version, type_id))
     })
 }
 
-fn parse_packet_length(bits: &[u8]) -> ParseOption<PacketLength> {
+fn parse_packet_length(bits: &[u8]) -> ParseResult<'_, PacketLength> {
     take_as_number(1, bits).and_then(|(bit, bits)| match bit {
         0 => take_as_number(15, bits).map_front(PacketLength::Bits),
-        1 => take_as_number(11, bits).map_front(PacketLength::Packets),
-        _ => None,
+        _ => take_as_number(11, bits).map_front(PacketLength::Packets),
     })
 }
 
-fn parse_literal_segment(bits: &[u8]) -> ParseOption<LiteralSegment> {
+fn parse_literal_segment(bits: &[u8]) -> ParseResult<'_, LiteralSegment> {
     take_as_number(1, bits).and_then(|(bit, bits)| match bit {
         0 => take_as_number(4, bits).map_front(LiteralSegment::End),
-        1 => take_as_number(4, bits).map_front(LiteralSegment::Part),
-        _ => None,
+        _ => take_as_number(4, bits).map_front(LiteralSegment::Part),
     })
 }
 
-fn parse_packet(bits: &[u8]) -> ParseOption<Packet> {
-    parse_literal_packet(bits).or_else(|| parse_operator_packet(bits))
+fn parse_packet(bits: &[u8]) -> ParseResult<'_, Packet> {
+    either(parse_literal_packet, parse_operator_packet, bits)
 }
 
-fn parse_literal_packet(bits: &[u8]) -> ParseOption<Packet> {
-    require(|header| header.type_id.is_literal(), parse_header, bits).and_then(|(header, bits)| {
+fn parse_literal_packet(bits: &[u8]) -> ParseResult<'_, Packet> {
+    require(
+        "a literal packet header",
+        |header| header.type_id.is_literal(),
+        parse_header,
+        bits,
+    )
+    .and_then(|(header, bits)| {
         parse_until(
             |segment| matches!(segment, LiteralSegment::End(_)),
             parse_literal_segment,
@@ -322,17 +262,46 @@ fn parse_literal_packet(bits: &[u8]) -> ParseOption<Packet> {
     })
 }
 
-fn parse_operator_packet(bits: &[u8]) -> ParseOption<Packet> {
-    require(|header| header.type_id.is_operator(), parse_header, bits).and_then(|(header, bits)| {
-        parse_packet_length(bits)
-            .and_then(|(length, bits)| match length {
-                PacketLength::Bits(n) => take(n, bits).and_then(|(sub_packet_bits, bits)| {
-                    parse_one_or_more(parse_packet, sub_packet_bits)
-                        .map(|(sub_packets, _)| (sub_packets, bits))
-                }),
-                PacketLength::Packets(n) => parse_exactly(n, parse_packet, bits),
-            })
-            .map_front(|sub_packets| Packet::operator(header, sub_packets))
+fn parse_operator_packet(bits: &[u8]) -> ParseResult<'_, Packet> {
+    require(
+        "an operator packet header",
+        |header| header.type_id.is_operator(),
+        parse_header,
+        bits,
+    )
+    .and_then(|(header, bits)| {
+        require(
+            sub_packet_count(header.type_id),
+            |sub_packets: &Vec<Packet>| match header.type_id.is_comparison() {
+                true => sub_packets.len() == 2,
+                false => !sub_packets.is_empty(),
+            },
+            parse_sub_packets,
+            bits,
+        )
+        .map_front(|sub_packets| Packet::operator(header, sub_packets))
+    })
+}
+
+/// Describes the sub-packets that an operator packet of the given type must have.
+fn sub_packet_count(packet_type: PacketType) -> &'static str {
+    match packet_type.is_comparison() {
+        true => "exactly two sub-packets",
+        false => "at least one sub-packet",
+    }
+}
+
+fn parse_sub_packets(bits: &[u8]) -> ParseResult<'_, Vec<Packet>> {
+    parse_packet_length(bits).and_then(|(length, bits)| match length {
+        PacketLength::Bits(n) => take(n, bits).and_then(|(sub_packet_bits, bits)| {
+            parse_all(
+                |bits| parse_one_or_more(parse_packet, bits),
+                sub_packet_bits,
+            )
+            .map(|sub_packets| (sub_packets, bits))
+            .map_err(|e| e.followed_by(bits))
+        }),
+        PacketLength::Packets(n) => parse_exactly(n, parse_packet, bits),
     })
 }
 
@@ -340,16 +309,65 @@ fn parse_operator_packet(bits: &[u8]) -> ParseOption<Packet> {
 // Top-level parser
 // =====================================================================================
 
-fn parse(bits: &[u8]) -> Option<Packet> {
+/// Parses the outermost packet, ignoring the zero bits that pad the transmission
+/// out to a whole number of hexadecimal digits.
+fn parse(bits: &[u8]) -> Result<Packet, Failure> {
     parse_packet(bits).map(|(packet, _)| packet)
 }
 
+impl Packet {
+    /// Decodes a hexadecimal transmission, like the puzzle input, locating any error at
+    /// the hexadecimal digit where it occurs.
+    pub fn from_hex(hex: &str) -> Result<Self, parse::ParseError> {
+        let digits = hex.trim();
+        let start = hex.len() - hex.trim_start().len();
+        let bits = hex_bits(hex, digits)?;
+        parse(&bits)
+            .map_err(|e| parse::ParseError::at(hex, start + e.offset(&bits) / 4, e.expected()))
+    }
+}
+
 #[aoc(day16, part1, nordzilla)]
-fn solve_part1(bits: &Input) -> Output {
-    parse(bits).unwrap().version_sum()
+fn solve_part1(packet: &Input) -> Output {
+    packet.version_sum()
 }
 
 #[aoc(day16, part2, nordzilla)]
-fn solve_part2(bits: &Input) -> Output {
-    parse(bits).unwrap().eval()
+fn solve_part2(packet: &Input) -> Output {
+    packet.eval()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_input_line_endings() {
+        let packet = input_generator("D2FE28").unwrap();
+        assert_eq!(input_generator("D2FE28\n").unwrap(), packet);
+        assert_eq!(input_generator("D2FE28\r\n").unwrap(), packet);
+        let error = input_generator("D2FE28\r\nD2").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+    }
+
+    #[test]
+    fn test_malformed_transmissions() {
+        for (input, column) in [("0", 1), ("D2FE", 5), ("38006F45", 6)] {
+            let error = input_generator(input).unwrap_err();
+            assert_eq!((error.line, error.column), (1, column), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_operators_without_enough_sub_packets() {
+        // sum(min()), where the minimum packet counts zero sub-packets.
+        let error = input_generator("020042800").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+        assert_eq!(error.expected, "at least one sub-packet");
+
+        // A less-than packet whose only sub-packet is the literal 1.
+        let error = input_generator("1A004408").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 2));
+        assert_eq!(error.expected, "exactly two sub-packets");
+    }
 }
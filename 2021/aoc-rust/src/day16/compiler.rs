@@ -0,0 +1,209 @@
+//! Compiles infix expressions such as `max(3, 5 * 7) > 10` into packet trees.
+//!
+//! ```text
+//! expression := sum (("<" | ">" | "==") sum)?
+//! sum        := product ("+" product)*
+//! product    := atom ("*" atom)*
+//! atom       := number | function "(" expression ("," expression)* ")" | "(" expression ")"
+//! function   := "sum" | "product" | "min" | "max"
+//! ```
+//!
+//! Every packet is given version zero.
+
+use super::{Header, Packet, PacketType};
+use aoc_common::combinator::{self, *};
+use num::BigUint;
+
+type ParseResult<'a, T> = combinator::ParseResult<'a, u8, T>;
+
+const COMPARISONS: [(&str, PacketType); 3] = [
+    ("<", PacketType::LessThan),
+    (">", PacketType::GreaterThan),
+    ("==", PacketType::EqualTo),
+];
+
+const FUNCTIONS: [(&str, PacketType); 4] = [
+    ("sum", PacketType::Sum),
+    ("product", PacketType::Product),
+    ("min", PacketType::Minimum),
+    ("max", PacketType::Maximum),
+];
+
+fn operator(packet_type: PacketType, sub_packets: Vec<Packet>) -> Packet {
+    Packet::operator(Header::new(0, packet_type), sub_packets)
+}
+
+// =====================================================================================
+// Tokens
+// =====================================================================================
+
+fn skip_whitespace(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn parse_symbol<'a>(symbol: &str, bytes: &'a [u8]) -> ParseResult<'a, ()> {
+    let bytes = skip_whitespace(bytes);
+    bytes
+        .strip_prefix(symbol.as_bytes())
+        .map(|rest| ((), rest))
+        .ok_or_else(|| Failure::new(format!("'{}'", symbol), bytes))
+}
+
+fn parse_number(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    let bytes = skip_whitespace(bytes);
+    take_while("a number", u8::is_ascii_digit, bytes).map_front(|digits| {
+        let value = BigUint::parse_bytes(digits, 10).unwrap();
+        Packet::literal_value(Header::new(0, PacketType::Literal), value)
+    })
+}
+
+// =====================================================================================
+// Expressions
+// =====================================================================================
+
+fn parse_expression(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    let (lhs, bytes) = parse_sum(bytes)?;
+    for (symbol, packet_type) in COMPARISONS {
+        if let Ok((_, bytes)) = parse_symbol(symbol, bytes) {
+            return parse_sum(bytes).map_front(|rhs| operator(packet_type, vec![lhs, rhs]));
+        }
+    }
+    Ok((lhs, bytes))
+}
+
+/// Parses operands separated by the symbol, collecting two or more of them into a single
+/// operator packet of the given type.
+fn parse_chain<'a>(
+    symbol: &str,
+    packet_type: PacketType,
+    parse_operand: impl Fn(&'a [u8]) -> ParseResult<'a, Packet>,
+    bytes: &'a [u8],
+) -> ParseResult<'a, Packet> {
+    let (first, mut bytes) = parse_operand(bytes)?;
+    let mut operands = vec![first];
+    while let Ok((_, rest)) = parse_symbol(symbol, bytes) {
+        let (operand, rest) = parse_operand(rest)?;
+        operands.push(operand);
+        bytes = rest;
+    }
+    match operands.len() {
+        1 => Ok((operands.pop().unwrap(), bytes)),
+        _ => Ok((operator(packet_type, operands), bytes)),
+    }
+}
+
+fn parse_sum(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    parse_chain("+", PacketType::Sum, parse_product, bytes)
+}
+
+fn parse_product(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    parse_chain("*", PacketType::Product, parse_atom, bytes)
+}
+
+fn parse_atom(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    either(
+        parse_number,
+        |bytes| either(parse_call, parse_group, bytes),
+        bytes,
+    )
+}
+
+fn parse_group(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    let (_, bytes) = parse_symbol("(", bytes)?;
+    let (packet, bytes) = parse_expression(bytes)?;
+    let (_, bytes) = parse_symbol(")", bytes)?;
+    Ok((packet, bytes))
+}
+
+fn parse_call(bytes: &[u8]) -> ParseResult<'_, Packet> {
+    let bytes = skip_whitespace(bytes);
+    let (name, rest) = take_while("a function", u8::is_ascii_alphabetic, bytes)?;
+    let packet_type = FUNCTIONS
+        .iter()
+        .find(|(function, _)| function.as_bytes() == name)
+        .map(|&(_, packet_type)| packet_type)
+        .ok_or_else(|| Failure::new("a known function", bytes))?;
+    let (_, bytes) = parse_symbol("(", rest)?;
+    let (first, mut bytes) = parse_expression(bytes)?;
+    let mut arguments = vec![first];
+    while let Ok((_, rest)) = parse_symbol(",", bytes) {
+        let (argument, rest) = parse_expression(rest)?;
+        arguments.push(argument);
+        bytes = rest;
+    }
+    let (_, bytes) = parse_symbol(")", bytes)?;
+    Ok((operator(packet_type, arguments), bytes))
+}
+
+/// Compiles an infix expression into the packet tree that evaluates it.
+pub fn compile(source: &str) -> Result<Packet, Failure> {
+    parse_all(
+        |bytes| parse_expression(bytes).map(|(packet, bytes)| (packet, skip_whitespace(bytes))),
+        source.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{input_generator, LengthType};
+    use super::*;
+
+    fn test_pipeline(source: &str, expected: usize) {
+        let packet = compile(source).unwrap();
+        assert_eq!(packet.eval(), expected, "{}", source);
+        for length_type in [LengthType::Bits, LengthType::Packets] {
+            let hex = packet.to_hex(length_type).unwrap();
+            let decoded = input_generator(&hex).unwrap();
+            assert_eq!(decoded, packet, "{}", source);
+            assert_eq!(decoded.eval(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_expressions() {
+        test_pipeline("max(3, 5 * 7) > 10", 1);
+        test_pipeline("1 + 2 * 3", 7);
+        test_pipeline("(1 + 2) * 3", 9);
+        test_pipeline("min(4, 2, 9) + max(1)", 3);
+        test_pipeline("sum(1, 2, 3) == product(1, 2, 3)", 1);
+        test_pipeline("3 < 2", 0);
+        test_pipeline("(1 < 2) + (2 < 1)", 1);
+        test_pipeline("  42  ", 42);
+    }
+
+    #[test]
+    fn test_puzzle_examples() {
+        // 9C0141080250320F1802104A08 from the puzzle.
+        test_pipeline("1 + 3 == 2 * 2", 1);
+        // CE00C43D881120 from the puzzle.
+        test_pipeline("max(7, 8, 9)", 9);
+    }
+
+    #[test]
+    fn test_flattens_chains() {
+        let packet = compile("1 + 2 + 3").unwrap();
+        assert_eq!(packet.header.type_id, PacketType::Sum);
+        assert_eq!(packet.sub_packets.len(), 3);
+    }
+
+    fn test_compile_error(source: &str, expected: &str, offset: usize) {
+        let error = compile(source).unwrap_err();
+        assert_eq!(error.expected(), expected, "{}", source);
+        assert_eq!(error.offset(source.as_bytes()), offset, "{}", source);
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let atom = "a number or a function or '('";
+        test_compile_error("", atom, 0);
+        test_compile_error("1 + ", atom, 4);
+        test_compile_error("max(1, 2", "')'", 8);
+        test_compile_error("1 < 2 < 3", "end of input", 6);
+        test_compile_error("(1 + 2", "')'", 6);
+        test_compile_error("maximum(1)", "a number or a known function or '('", 0);
+    }
+}
@@ -0,0 +1,326 @@
+use super::{Header, Packet, PacketType};
+use aoc_common::num::from_bits;
+use num::BigUint;
+use std::{error::Error, fmt, iter};
+
+const VERSION_WIDTH: usize = 3;
+const TYPE_ID_WIDTH: usize = 3;
+const BIT_LENGTH_WIDTH: usize = 15;
+const PACKET_COUNT_WIDTH: usize = 11;
+
+// =====================================================================================
+// Types
+// =====================================================================================
+
+/// How an operator packet records the extent of its sub-packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthType {
+    /// The total length of the sub-packets in bits, as in `PacketLength::Bits`.
+    Bits,
+    /// The number of sub-packets, as in `PacketLength::Packets`.
+    Packets,
+}
+
+/// A packet that cannot be represented in the BITS transmission format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    VersionOutOfRange(usize),
+    NoSubPackets,
+    TooManyBits(usize),
+    TooManyPackets(usize),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::VersionOutOfRange(version) => {
+                write!(
+                    f,
+                    "version {} does not fit in {} bits",
+                    version, VERSION_WIDTH
+                )
+            }
+            EncodeError::NoSubPackets => write!(f, "operator packet has no sub-packets"),
+            EncodeError::TooManyBits(n) => {
+                write!(
+                    f,
+                    "{} bits of sub-packets do not fit in {} bits",
+                    n, BIT_LENGTH_WIDTH
+                )
+            }
+            EncodeError::TooManyPackets(n) => {
+                write!(
+                    f,
+                    "{} sub-packets do not fit in {} bits",
+                    n, PACKET_COUNT_WIDTH
+                )
+            }
+        }
+    }
+}
+
+impl Error for EncodeError {}
+
+// =====================================================================================
+// Encoder
+// =====================================================================================
+
+fn push_number(bits: &mut Vec<u8>, n: usize, width: usize) {
+    bits.extend((0..width).rev().map(|i| (n >> i & 1) as u8));
+}
+
+fn push_literal(bits: &mut Vec<u8>, value: &BigUint) {
+    let nibbles = value.to_radix_be(16);
+    for (i, &nibble) in nibbles.iter().enumerate() {
+        bits.push((i + 1 < nibbles.len()) as u8);
+        push_number(bits, nibble as usize, 4);
+    }
+}
+
+fn type_id(packet_type: PacketType) -> usize {
+    match packet_type {
+        PacketType::Sum => 0,
+        PacketType::Product => 1,
+        PacketType::Minimum => 2,
+        PacketType::Maximum => 3,
+        PacketType::Literal => 4,
+        PacketType::GreaterThan => 5,
+        PacketType::LessThan => 6,
+        PacketType::EqualTo => 7,
+    }
+}
+
+/// Renders bits as hexadecimal digits, padding the last digit with zero bits.
+pub fn to_hex(bits: &[u8]) -> String {
+    bits.chunks(4)
+        .map(|chunk| {
+            let digit: Vec<u8> = chunk
+                .iter()
+                .copied()
+                .chain(iter::repeat(0))
+                .take(4)
+                .collect();
+            char::from_digit(from_bits(&digit) as u32, 16)
+                .unwrap()
+                .to_ascii_uppercase()
+        })
+        .collect()
+}
+
+impl Packet {
+    /// Encodes the packet as bits, recording every operator's sub-packets with the given length type.
+    pub fn encode(&self, length_type: LengthType) -> Result<Vec<u8>, EncodeError> {
+        self.encode_with(&|_| length_type)
+    }
+
+    /// Encodes the packet as bits, choosing the length type for each operator packet.
+    pub fn encode_with(
+        &self,
+        length_type: &impl Fn(&Packet) -> LengthType,
+    ) -> Result<Vec<u8>, EncodeError> {
+        let mut bits = Vec::new();
+        self.encode_into(&mut bits, length_type)?;
+        Ok(bits)
+    }
+
+    /// Encodes the packet as a hexadecimal transmission, like the puzzle input.
+    pub fn to_hex(&self, length_type: LengthType) -> Result<String, EncodeError> {
+        self.encode(length_type).map(|bits| to_hex(&bits))
+    }
+
+    fn encode_into(
+        &self,
+        bits: &mut Vec<u8>,
+        length_type: &impl Fn(&Packet) -> LengthType,
+    ) -> Result<(), EncodeError> {
+        let Header {
+            version,
+            type_id: packet_type,
+        } = self.header;
+        if version >= 1 << VERSION_WIDTH {
+            return Err(EncodeError::VersionOutOfRange(version));
+        }
+        push_number(bits, version, VERSION_WIDTH);
+        push_number(bits, type_id(packet_type), TYPE_ID_WIDTH);
+
+        if packet_type.is_literal() {
+            push_literal(bits, &self.value);
+            return Ok(());
+        }
+        if self.sub_packets.is_empty() {
+            return Err(EncodeError::NoSubPackets);
+        }
+
+        match length_type(self) {
+            LengthType::Bits => {
+                let mut sub_packet_bits = Vec::new();
+                for sub_packet in &self.sub_packets {
+                    sub_packet.encode_into(&mut sub_packet_bits, length_type)?;
+                }
+                if sub_packet_bits.len() >= 1 << BIT_LENGTH_WIDTH {
+                    return Err(EncodeError::TooManyBits(sub_packet_bits.len()));
+                }
+                bits.push(0);
+                push_number(bits, sub_packet_bits.len(), BIT_LENGTH_WIDTH);
+                bits.extend(sub_packet_bits);
+            }
+            LengthType::Packets => {
+                if self.sub_packets.len() >= 1 << PACKET_COUNT_WIDTH {
+                    return Err(EncodeError::TooManyPackets(self.sub_packets.len()));
+                }
+                bits.push(1);
+                push_number(bits, self.sub_packets.len(), PACKET_COUNT_WIDTH);
+                for sub_packet in &self.sub_packets {
+                    sub_packet.encode_into(bits, length_type)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{input_generator, parse};
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn decode(hex: &str) -> Packet {
+        input_generator(hex).unwrap()
+    }
+
+    fn literal(version: usize, value: usize) -> Packet {
+        Packet::literal_value(
+            Header::new(version, PacketType::Literal),
+            BigUint::from(value),
+        )
+    }
+
+    fn operator(version: usize, packet_type: PacketType, sub_packets: Vec<Packet>) -> Packet {
+        Packet::operator(Header::new(version, packet_type), sub_packets)
+    }
+
+    /// Generates a random packet tree whose operators are nested at most `max_depth` deep.
+    ///
+    /// Comparison operators always get two sub-packets, as the puzzle requires, and the
+    /// other operators get between one and four.
+    fn random_packet(rng: &mut impl Rng, max_depth: usize) -> Packet {
+        let version = rng.gen_range(0..1 << VERSION_WIDTH);
+        if max_depth == 0 || rng.gen_bool(0.25) {
+            let value = rng.gen::<usize>() >> rng.gen_range(0..usize::BITS);
+            let header = Header::new(version, PacketType::Literal);
+            return Packet::literal_value(header, BigUint::from(value));
+        }
+
+        let packet_type = match rng.gen_range(0..7) {
+            0 => PacketType::Sum,
+            1 => PacketType::Product,
+            2 => PacketType::Minimum,
+            3 => PacketType::Maximum,
+            4 => PacketType::GreaterThan,
+            5 => PacketType::LessThan,
+            _ => PacketType::EqualTo,
+        };
+        let sub_packet_count = match packet_type {
+            PacketType::GreaterThan | PacketType::LessThan | PacketType::EqualTo => 2,
+            _ => rng.gen_range(1..=4),
+        };
+        let sub_packets = (0..sub_packet_count)
+            .map(|_| random_packet(rng, max_depth - 1))
+            .collect();
+        Packet::operator(Header::new(version, packet_type), sub_packets)
+    }
+
+    #[test]
+    fn test_puzzle_examples() {
+        let packet = decode("D2FE28");
+        assert_eq!(packet, literal(6, 2021));
+        assert_eq!(packet.to_hex(LengthType::Bits).unwrap(), "D2FE28");
+
+        let packet = decode("38006F45291200");
+        assert_eq!(
+            packet,
+            operator(
+                1,
+                PacketType::LessThan,
+                vec![literal(6, 10), literal(2, 20)]
+            ),
+        );
+        // The puzzle pads this transmission with more zeros than a whole hex digit needs.
+        assert_eq!(packet.to_hex(LengthType::Bits).unwrap(), "38006F4529120");
+
+        let packet = decode("EE00D40C823060");
+        assert_eq!(
+            packet,
+            operator(
+                7,
+                PacketType::Maximum,
+                vec![literal(2, 1), literal(4, 2), literal(1, 3)],
+            ),
+        );
+        assert_eq!(packet.to_hex(LengthType::Packets).unwrap(), "EE00D40C82306");
+    }
+
+    #[test]
+    fn test_literal_segments() {
+        for value in [0, 1, 15, 16, 2021, usize::MAX] {
+            assert_eq!(
+                decode(&literal(0, value).to_hex(LengthType::Bits).unwrap()).value,
+                BigUint::from(value),
+            );
+        }
+        assert_eq!(literal(0, 0).encode(LengthType::Bits).unwrap().len(), 11);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..200 {
+            let packet = random_packet(&mut rng, 6);
+            for length_type in [LengthType::Bits, LengthType::Packets] {
+                assert_eq!(decode(&packet.to_hex(length_type).unwrap()), packet);
+            }
+            let mixed = packet
+                .encode_with(&|packet: &Packet| match packet.sub_packets.len() % 2 {
+                    0 => LengthType::Bits,
+                    _ => LengthType::Packets,
+                })
+                .unwrap();
+            assert_eq!(parse(&mixed).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn test_deep_packets() {
+        let nested = |depth| {
+            (0..depth).fold(literal(0, 1), |packet, _| {
+                operator(0, PacketType::Sum, vec![packet])
+            })
+        };
+        let packet = nested(500);
+        assert_eq!(decode(&packet.to_hex(LengthType::Packets).unwrap()), packet);
+        assert_eq!(decode(&packet.to_hex(LengthType::Bits).unwrap()), packet);
+
+        // Each level adds a 22-bit operator header to the 11-bit literal at the bottom.
+        assert_eq!(
+            nested(1490).encode(LengthType::Bits),
+            Err(EncodeError::TooManyBits(11 + 22 * 1489)),
+        );
+    }
+
+    #[test]
+    fn test_unencodable_packets() {
+        assert_eq!(
+            literal(8, 0).encode(LengthType::Bits),
+            Err(EncodeError::VersionOutOfRange(8)),
+        );
+        assert_eq!(
+            operator(0, PacketType::Sum, vec![]).encode(LengthType::Packets),
+            Err(EncodeError::NoSubPackets),
+        );
+        assert_eq!(
+            operator(0, PacketType::Sum, vec![literal(0, 0); 2048]).encode(LengthType::Packets),
+            Err(EncodeError::TooManyPackets(2048)),
+        );
+    }
+}
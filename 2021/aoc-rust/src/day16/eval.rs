@@ -0,0 +1,168 @@
+use super::{Header, Packet, PacketType};
+use num::{BigUint, CheckedAdd, CheckedMul, One, ToPrimitive, Zero};
+use std::{error::Error, fmt};
+
+// =====================================================================================
+// Types
+// =====================================================================================
+
+/// A number type that packets can be evaluated in.
+pub trait Value: Ord + Zero + One + CheckedAdd + CheckedMul {
+    /// Converts a literal packet's value, or returns `None` if it does not fit.
+    fn from_literal(literal: &BigUint) -> Option<Self>;
+}
+
+impl Value for usize {
+    fn from_literal(literal: &BigUint) -> Option<Self> {
+        literal.to_usize()
+    }
+}
+
+impl Value for u128 {
+    fn from_literal(literal: &BigUint) -> Option<Self> {
+        literal.to_u128()
+    }
+}
+
+impl Value for BigUint {
+    fn from_literal(literal: &BigUint) -> Option<Self> {
+        Some(literal.clone())
+    }
+}
+
+/// A packet whose value does not fit in the type that it was evaluated in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    /// The index of each sub-packet on the way from the outermost packet to this one.
+    pub path: Vec<usize>,
+    pub header: Header,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} packet (version {}) at sub-packet path {:?} overflowed",
+            self.header.type_id, self.header.version, self.path,
+        )
+    }
+}
+
+impl Error for Overflow {}
+
+// =====================================================================================
+// Evaluation
+// =====================================================================================
+
+impl Packet {
+    /// Evaluates the packet, panicking if any packet's value does not fit in a `usize`.
+    pub fn eval(&self) -> usize {
+        self.checked_eval()
+            .unwrap_or_else(|overflow| panic!("{}", overflow))
+    }
+
+    /// Evaluates the packet, reporting the first packet whose value does not fit in a `usize`.
+    pub fn checked_eval(&self) -> Result<usize, Overflow> {
+        self.eval_as()
+    }
+
+    /// Evaluates the packet with arbitrary precision.
+    pub fn eval_big(&self) -> BigUint {
+        self.eval_as().unwrap()
+    }
+
+    /// Evaluates the packet in any value type, reporting the first packet whose value
+    /// does not fit in it.
+    pub fn eval_as<V: Value>(&self) -> Result<V, Overflow> {
+        self.eval_at(&mut Vec::new())
+    }
+
+    fn eval_at<V: Value>(&self, path: &mut Vec<usize>) -> Result<V, Overflow> {
+        let overflow = |path: &[usize]| Overflow {
+            path: path.to_vec(),
+            header: self.header,
+        };
+        if self.header.type_id.is_literal() {
+            return V::from_literal(&self.value).ok_or_else(|| overflow(path));
+        }
+
+        let mut sub_packet_values = Vec::with_capacity(self.sub_packets.len());
+        for (i, sub_packet) in self.sub_packets.iter().enumerate() {
+            path.push(i);
+            sub_packet_values.push(sub_packet.eval_at(path)?);
+            path.pop();
+        }
+
+        // Packets are only built by the parser and the compiler, which both guarantee that
+        // every operator has a sub-packet and every comparison has exactly two.
+        let from_bool = |b| if b { V::one() } else { V::zero() };
+        let mut sub_packet_values = sub_packet_values.into_iter();
+        match self.header.type_id {
+            PacketType::Sum => sub_packet_values
+                .try_fold(V::zero(), |sum, value| sum.checked_add(&value))
+                .ok_or_else(|| overflow(path)),
+            PacketType::Product => sub_packet_values
+                .try_fold(V::one(), |product, value| product.checked_mul(&value))
+                .ok_or_else(|| overflow(path)),
+            PacketType::Minimum => Ok(sub_packet_values.min().unwrap()),
+            PacketType::Maximum => Ok(sub_packet_values.max().unwrap()),
+            PacketType::Literal => unreachable!(),
+            PacketType::GreaterThan => Ok(from_bool(
+                sub_packet_values.next().unwrap() > sub_packet_values.next().unwrap(),
+            )),
+            PacketType::LessThan => Ok(from_bool(
+                sub_packet_values.next().unwrap() < sub_packet_values.next().unwrap(),
+            )),
+            PacketType::EqualTo => Ok(from_bool(
+                sub_packet_values.next().unwrap() == sub_packet_values.next().unwrap(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{compile, LengthType};
+    use super::*;
+
+    #[test]
+    fn test_fits_in_usize() {
+        let packet = compile("max(3, 5 * 7) > 10").unwrap();
+        assert_eq!(packet.checked_eval(), Ok(1));
+        assert_eq!(packet.eval_big(), BigUint::from(1u8));
+    }
+
+    #[test]
+    fn test_overflowing_product() {
+        let packet = compile("1 + max(2, 4294967296 * 4294967296)").unwrap();
+        let overflow = packet.checked_eval().unwrap_err();
+        assert_eq!(overflow.path, [1, 1]);
+        assert_eq!(overflow.header.type_id, PacketType::Product);
+        assert_eq!(packet.eval_as::<u128>(), Ok(1 + (1 << 64)));
+        assert_eq!(packet.eval_big(), BigUint::from(1u128 + (1 << 64)));
+    }
+
+    #[test]
+    fn test_overflowing_sum() {
+        let packet = compile(&format!("{} + 1", usize::MAX)).unwrap();
+        let overflow = packet.checked_eval().unwrap_err();
+        assert!(overflow.path.is_empty());
+        assert_eq!(overflow.header.type_id, PacketType::Sum);
+        assert_eq!(packet.eval_big(), BigUint::from(usize::MAX) + 1u8);
+    }
+
+    #[test]
+    fn test_long_literals() {
+        let literal = "123456789012345678901234567890123456789";
+        let packet = compile(&format!("{} < 1", literal)).unwrap();
+        let decoded = Packet::from_hex(&packet.to_hex(LengthType::Bits).unwrap()).unwrap();
+        let overflow = decoded.checked_eval().unwrap_err();
+        assert_eq!(overflow.path, [0]);
+        assert_eq!(overflow.header.type_id, PacketType::Literal);
+        assert_eq!(decoded.eval_big(), BigUint::zero());
+        assert_eq!(
+            decoded.sub_packets[0].eval_big(),
+            literal.parse::<BigUint>().unwrap(),
+        );
+    }
+}
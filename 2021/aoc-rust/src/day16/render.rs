@@ -0,0 +1,153 @@
+use super::{Packet, PacketType};
+use std::fmt::{self, Write};
+
+/// The width that the alternate display tries to keep each line within.
+const LINE_WIDTH: usize = 80;
+
+fn symbol(packet_type: PacketType) -> &'static str {
+    match packet_type {
+        PacketType::Sum => "+",
+        PacketType::Product => "*",
+        PacketType::Minimum => "min",
+        PacketType::Maximum => "max",
+        PacketType::Literal => "literal",
+        PacketType::GreaterThan => ">",
+        PacketType::LessThan => "<",
+        PacketType::EqualTo => "==",
+    }
+}
+
+// =====================================================================================
+// S-expressions
+// =====================================================================================
+
+impl Packet {
+    fn write_compact(&self, f: &mut impl Write) -> fmt::Result {
+        if self.header.type_id.is_literal() {
+            return write!(f, "{}", self.value);
+        }
+        write!(f, "({}", symbol(self.header.type_id))?;
+        for sub_packet in &self.sub_packets {
+            write!(f, " ")?;
+            sub_packet.write_compact(f)?;
+        }
+        write!(f, ")")
+    }
+
+    fn write_pretty(&self, f: &mut impl Write, indent: usize) -> fmt::Result {
+        let mut compact = String::new();
+        self.write_compact(&mut compact)?;
+        if indent + compact.len() <= LINE_WIDTH || self.header.type_id.is_literal() {
+            return write!(f, "{}", compact);
+        }
+        write!(f, "({}", symbol(self.header.type_id))?;
+        for sub_packet in &self.sub_packets {
+            write!(f, "\n{:indent$}", "", indent = indent + 2)?;
+            sub_packet.write_pretty(f, indent + 2)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Displays the packet as an S-expression such as `(+ (* 18519 (== 43 78927470)) ...)`.
+///
+/// The alternate form `{:#}` breaks operators that do not fit on one line into one
+/// indented sub-packet per line.
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            self.write_pretty(f, 0)
+        } else {
+            self.write_compact(f)
+        }
+    }
+}
+
+// =====================================================================================
+// Graphviz
+// =====================================================================================
+
+impl Packet {
+    /// Renders the packet tree as a Graphviz digraph, labelling each node with its
+    /// operator or literal value, its version, and the value that it evaluates to.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph packet {\n    node [shape=box];\n");
+        self.write_dot_node(&mut dot, &mut 0);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes this packet's node and the edges to its sub-packets, returning its node id.
+    fn write_dot_node(&self, dot: &mut String, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let label = match self.header.type_id {
+            PacketType::Literal => format!("{}\\nversion {}", self.value, self.header.version),
+            packet_type => format!(
+                "{}\\nversion {}\\n= {}",
+                symbol(packet_type),
+                self.header.version,
+                self.eval_big(),
+            ),
+        };
+        writeln!(dot, "    n{} [label=\"{}\"];", id, label).unwrap();
+        for sub_packet in &self.sub_packets {
+            let sub_id = sub_packet.write_dot_node(dot, next_id);
+            writeln!(dot, "    n{} -> n{};", id, sub_id).unwrap();
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::compile;
+
+    #[test]
+    fn test_compact() {
+        let packet = compile("1 + 3 == 2 * 2").unwrap();
+        assert_eq!(packet.to_string(), "(== (+ 1 3) (* 2 2))");
+        let packet = compile("max(3, 5 * 7) > min(10)").unwrap();
+        assert_eq!(packet.to_string(), "(> (max 3 (* 5 7)) (min 10))");
+        assert_eq!(compile("42").unwrap().to_string(), "42");
+    }
+
+    #[test]
+    fn test_pretty() {
+        let packet = compile("1 + 2").unwrap();
+        assert_eq!(format!("{:#}", packet), "(+ 1 2)");
+
+        let long = "1000000000 * 1000000000";
+        let packet = compile(&format!("sum({0}, {0}, max({0}, {0}))", long)).unwrap();
+        assert_eq!(
+            format!("{:#}", packet),
+            "(+\n  (* 1000000000 1000000000)\n  (* 1000000000 1000000000)\n  \
+             (max (* 1000000000 1000000000) (* 1000000000 1000000000)))",
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let packet = compile("max(3, 5 * 7) > 10").unwrap();
+        assert_eq!(
+            packet.to_dot(),
+            "digraph packet {
+    node [shape=box];
+    n0 [label=\">\\nversion 0\\n= 1\"];
+    n1 [label=\"max\\nversion 0\\n= 35\"];
+    n2 [label=\"3\\nversion 0\"];
+    n1 -> n2;
+    n3 [label=\"*\\nversion 0\\n= 35\"];
+    n4 [label=\"5\\nversion 0\"];
+    n3 -> n4;
+    n5 [label=\"7\\nversion 0\"];
+    n3 -> n5;
+    n1 -> n3;
+    n0 -> n1;
+    n6 [label=\"10\\nversion 0\"];
+    n0 -> n6;
+}
+",
+        );
+    }
+}
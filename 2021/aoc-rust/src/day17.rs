@@ -1,6 +1,9 @@
+use aoc_common::{
+    num::Approach,
+    parse::{self, ParseError},
+};
 use aoc_runner_derive::*;
-use std::{cmp::Ordering, ops::RangeInclusive};
-use text_io::scan;
+use std::ops::RangeInclusive;
 
 type Input = (RangeInclusive<i64>, RangeInclusive<i64>);
 type Output = i64;
@@ -40,26 +43,12 @@ impl Probe {
     }
 }
 
-trait Approach {
-    fn approach(self, target: Self) -> Self;
-}
-
-impl Approach for i64 {
-    fn approach(self, target: Self) -> Self {
-        match target.cmp(&self) {
-            Ordering::Less => self - 1,
-            Ordering::Equal => self,
-            Ordering::Greater => self + 1,
-        }
-    }
-}
-
 #[aoc_generator(day17)]
-fn input_generator(raw_input: &str) -> Input {
-    let [x_min, x_max]: [i64; 2];
-    let [y_min, y_max]: [i64; 2];
-    scan!(raw_input.bytes() => "target area: x={}..{}, y={}..{}", x_min, x_max, y_min, y_max);
-    (x_min..=x_max, y_min..=y_max)
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    let [x_min, x_max, y_min, y_max] =
+        parse::scan(raw_input, raw_input, "target area: x={}..{}, y={}..{}")?
+            .map(|n| parse::value(raw_input, n, "a coordinate"));
+    Ok((x_min?..=x_max?, y_min?..=y_max?))
 }
 
 fn get_max_ys((x_range, y_range): &Input) -> Vec<i64> {
@@ -1,30 +1,71 @@
+use aoc_common::{
+    combinator::{self, *},
+    parse,
+};
 use aoc_runner_derive::*;
 use flow_control::return_if;
 use itertools::Itertools;
+use std::{cmp::Ordering, fmt, iter::Sum, ops::Add, str::FromStr};
+
+mod flat;
 
 type Input = Vec<SfNumber>;
 type Output = i64;
 
+/// A snailfish number: either a regular number or a pair of snailfish numbers.
+///
+/// Snailfish numbers are added with `+`, which reduces the sum as the puzzle describes,
+/// and parse from and display as the puzzle's notation, such as `[[1,2],3]`. They are
+/// ordered by magnitude, with numbers of equal magnitude ordered by their structure.
+///
+/// Summing an empty iterator of numbers panics, since snailfish addition has no identity.
+/// [`SfNumber::try_sum`] returns `None` instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SfNumber(Node);
+
+/// A snailfish number as a tree, where each node caches its height so that explosions
+/// can skip the subtrees that are too shallow to need one.
 #[derive(Debug, Clone)]
-pub enum SfNumber {
+enum Node {
     Single {
         height: i64,
         value: i64,
     },
     Pair {
         height: i64,
-        lhs: Box<SfNumber>,
-        rhs: Box<SfNumber>,
+        lhs: Box<Node>,
+        rhs: Box<Node>,
     },
 }
 
 impl SfNumber {
+    pub fn new_single(value: i64) -> Self {
+        SfNumber(Node::new_single(value))
+    }
+
+    pub fn new_pair(lhs: SfNumber, rhs: SfNumber) -> Self {
+        SfNumber(Node::new_pair(lhs.0, rhs.0))
+    }
+
+    /// A regular number's value, or three times the magnitude of a pair's left element
+    /// plus twice the magnitude of its right element.
+    pub fn magnitude(&self) -> i64 {
+        self.0.magnitude()
+    }
+
+    /// Adds up the numbers from left to right, or returns `None` if there are none.
+    pub fn try_sum(numbers: impl IntoIterator<Item = SfNumber>) -> Option<Self> {
+        numbers.into_iter().reduce(Add::add)
+    }
+}
+
+impl Node {
     fn new_single(value: i64) -> Self {
-        SfNumber::Single { height: 0, value }
+        Node::Single { height: 0, value }
     }
 
-    fn new_pair(lhs: SfNumber, rhs: SfNumber) -> Self {
-        SfNumber::Pair {
+    fn new_pair(lhs: Node, rhs: Node) -> Self {
+        Node::Pair {
             height: std::cmp::max(lhs.height(), rhs.height()) + 1,
             lhs: Box::new(lhs),
             rhs: Box::new(rhs),
@@ -32,87 +73,129 @@ impl SfNumber {
     }
 
     fn is_single(&self) -> bool {
-        matches!(self, SfNumber::Single { .. })
+        matches!(self, Node::Single { .. })
     }
 
     fn height(&self) -> i64 {
         match self {
-            SfNumber::Single { height, .. } => *height,
-            SfNumber::Pair { height, .. } => *height,
+            Node::Single { height, .. } => *height,
+            Node::Pair { height, .. } => *height,
         }
     }
 
-    #[cfg(test)]
-    fn to_string(&self) -> String {
-        match self {
-            SfNumber::Single { value, .. } => value.to_string(),
-            SfNumber::Pair { lhs, rhs, .. } => {
-                format!("[{},{}]", lhs.to_string(), rhs.to_string())
-            }
+    /// Compares the shape and values of two numbers: regular numbers before pairs, and
+    /// pairs by their left then their right element.
+    fn cmp_structure(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Node::Single { value: lhs, .. }, Node::Single { value: rhs, .. }) => lhs.cmp(rhs),
+            (Node::Single { .. }, Node::Pair { .. }) => Ordering::Less,
+            (Node::Pair { .. }, Node::Single { .. }) => Ordering::Greater,
+            (
+                Node::Pair { lhs, rhs, .. },
+                Node::Pair {
+                    lhs: other_lhs,
+                    rhs: other_rhs,
+                    ..
+                },
+            ) => lhs
+                .cmp_structure(other_lhs)
+                .then_with(|| rhs.cmp_structure(other_rhs)),
         }
     }
 }
 
-//==================================================================================
-// Parser
-//==================================================================================
-
-type ParseOption<'a, T> = Option<(T, &'a [u8])>;
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Single { value, .. } => write!(f, "{}", value),
+            Node::Pair { lhs, rhs, .. } => write!(f, "[{},{}]", lhs, rhs),
+        }
+    }
+}
 
-fn next(bytes: &[u8]) -> ParseOption<u8> {
-    (!bytes.is_empty())
-        .then(|| bytes.split_at(1))
-        .map(|(next, bytes)| (next[0], bytes))
+impl fmt::Display for SfNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
 }
 
-fn take_while(predicate: impl Fn(u8) -> bool, bytes: &[u8]) -> ParseOption<&[u8]> {
-    bytes
-        .iter()
-        .position(|&byte| !predicate(byte))
-        .and_then(|i| (i > 0).then(|| bytes.split_at(i)))
+// The height is a cached property of the tree, so it takes no part in comparisons.
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp_structure(other) == Ordering::Equal
+    }
 }
 
-fn require(predicate: impl Fn(u8) -> bool, bytes: &[u8]) -> ParseOption<u8> {
-    next(bytes).filter(|&(b, _)| predicate(b))
+impl Eq for Node {}
+
+impl PartialOrd for SfNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-fn parse_match(byte: u8, bytes: &[u8]) -> ParseOption<u8> {
-    require(|b| b == byte, bytes)
+impl Ord for SfNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.magnitude()
+            .cmp(&other.magnitude())
+            .then_with(|| self.0.cmp_structure(&other.0))
+    }
 }
 
-fn parse_value(bytes: &[u8]) -> ParseOption<i64> {
-    take_while(|byte| (b'0'..=b'9').contains(&byte), bytes).and_then(|(digits, bytes)| {
-        i64::from_str_radix(unsafe { std::str::from_utf8_unchecked(digits) }, 10)
-            .ok()
-            .map(|value| (value, bytes))
+//==================================================================================
+// Parser
+//==================================================================================
+
+type ParseResult<'a, T> = combinator::ParseResult<'a, u8, T>;
+
+fn parse_value(bytes: &[u8]) -> ParseResult<'_, i64> {
+    take_while("a digit", u8::is_ascii_digit, bytes).and_then(|(digits, rest)| {
+        std::str::from_utf8(digits)
+            .unwrap()
+            .parse()
+            .map(|value| (value, rest))
+            .map_err(|_| Failure::new("a value that fits in an i64", bytes))
     })
 }
 
-fn parse_sf_number(bytes: &[u8]) -> ParseOption<SfNumber> {
-    parse_single(bytes).or_else(|| parse_pair(bytes))
+fn parse_sf_number(bytes: &[u8]) -> ParseResult<'_, Node> {
+    either(parse_single, parse_pair, bytes)
 }
 
-fn parse_single(bytes: &[u8]) -> ParseOption<SfNumber> {
-    parse_value(bytes).map(|(value, bytes)| (SfNumber::new_single(value), bytes))
+fn parse_single(bytes: &[u8]) -> ParseResult<'_, Node> {
+    parse_value(bytes).map_front(Node::new_single)
 }
 
-fn parse_pair(bytes: &[u8]) -> ParseOption<SfNumber> {
-    parse_match(b'[', bytes).and_then(|(_, bytes)| {
+fn parse_pair(bytes: &[u8]) -> ParseResult<'_, Node> {
+    token(b'[', bytes).and_then(|(_, bytes)| {
         parse_sf_number(bytes).and_then(|(lhs, bytes)| {
-            parse_match(b',', bytes).and_then(|(_, bytes)| {
+            token(b',', bytes).and_then(|(_, bytes)| {
                 parse_sf_number(bytes).and_then(|(rhs, bytes)| {
-                    parse_match(b']', bytes).map(|(_, bytes)| (SfNumber::new_pair(lhs, rhs), bytes))
+                    token(b']', bytes).map(|(_, bytes)| (Node::new_pair(lhs, rhs), bytes))
                 })
             })
         })
     })
 }
 
+impl FromStr for SfNumber {
+    type Err = parse::ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(parse_sf_number, s.as_bytes())
+            .map(SfNumber)
+            .map_err(|e| e.locate(s, s))
+    }
+}
+
 #[aoc_generator(day18)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, parse::ParseError> {
     raw_input
         .lines()
-        .map(|line| parse_sf_number(line.as_bytes()).unwrap().0)
+        .map(|line| {
+            parse_all(parse_sf_number, line.as_bytes())
+                .map(SfNumber)
+                .map_err(|e| e.locate(raw_input, line))
+        })
         .collect()
 }
 
@@ -120,41 +203,67 @@ fn input_generator(raw_input: &str) -> Input {
 // Arithmetic
 //==================================================================================
 
-impl SfNumber {
+impl Node {
     fn magnitude(&self) -> i64 {
         match self {
-            SfNumber::Single { value, .. } => *value,
-            SfNumber::Pair { lhs, rhs, .. } => 3 * lhs.magnitude() + 2 * rhs.magnitude(),
+            Node::Single { value, .. } => *value,
+            Node::Pair { lhs, rhs, .. } => 3 * lhs.magnitude() + 2 * rhs.magnitude(),
         }
     }
 
-    fn add(self, rhs: SfNumber) -> Self {
-        SfNumber::new_pair(self, rhs).reduce()
+    fn reduce(mut self) -> Self {
+        loop {
+            let (number, action) = self.step(None);
+            self = number;
+            return_if!(action.is_none(), self);
+        }
     }
 
-    fn reduce(mut self) -> Self {
+    /// Explodes or else splits the leftmost pair or number that needs it, returning which
+    /// it did, if either. If given, `path` receives the steps from the root down to where
+    /// it acted.
+    fn step(self, mut path: Option<&mut Vec<Side>>) -> (Self, Option<Action>) {
         let mut exploded_values = None;
-        self = self.explode(5, &mut exploded_values);
-        if exploded_values.is_some() || self.split() {
-            return self.reduce();
+        let mut number = self.explode(5, &mut exploded_values, path.as_deref_mut());
+        let action = if exploded_values.is_some() {
+            Some(Action::Explode)
+        } else if number.split(path.as_deref_mut()) {
+            Some(Action::Split)
+        } else {
+            None
+        };
+        if let Some(path) = path {
+            path.reverse();
         }
-        self
+        (number, action)
     }
 
-    fn split(&mut self) -> bool {
+    /// Splits the leftmost regular number that is greater than 9, pushing the steps to it
+    /// onto `path`, if given, from the number up to the root.
+    fn split(&mut self, mut path: Option<&mut Vec<Side>>) -> bool {
         match self {
-            SfNumber::Single { value, .. } => {
+            Node::Single { value, .. } => {
                 return_if!(*value > 9, {
-                    *self = SfNumber::new_pair(
-                        SfNumber::new_single(*value / 2),
-                        SfNumber::new_single(*value / 2 + *value % 2),
+                    *self = Node::new_pair(
+                        Node::new_single(*value / 2),
+                        Node::new_single(*value / 2 + *value % 2),
                     );
                     true
                 });
                 false
             }
-            SfNumber::Pair { height, lhs, rhs } => {
-                let split = lhs.split() || rhs.split();
+            Node::Pair { height, lhs, rhs } => {
+                let side = if lhs.split(path.as_deref_mut()) {
+                    Some(Side::Left)
+                } else if rhs.split(path.as_deref_mut()) {
+                    Some(Side::Right)
+                } else {
+                    None
+                };
+                if let Some(path) = path {
+                    path.extend(side);
+                }
+                let split = side.is_some();
                 if split {
                     *height += 1;
                 }
@@ -166,11 +275,11 @@ impl SfNumber {
     fn accept_value_right(&mut self, n: &mut i64) {
         return_if!(*n == 0);
         match self {
-            SfNumber::Single { value, .. } => {
+            Node::Single { value, .. } => {
                 *value += *n;
                 *n = 0;
             }
-            SfNumber::Pair { rhs, .. } => {
+            Node::Pair { rhs, .. } => {
                 rhs.accept_value_right(n);
             }
         }
@@ -179,58 +288,167 @@ impl SfNumber {
     fn accept_value_left(&mut self, n: &mut i64) {
         return_if!(*n == 0);
         match self {
-            SfNumber::Single { value, .. } => {
+            Node::Single { value, .. } => {
                 *value += *n;
                 *n = 0;
             }
-            SfNumber::Pair { lhs, .. } => {
+            Node::Pair { lhs, .. } => {
                 lhs.accept_value_left(n);
             }
         }
     }
 
-    fn explode(self, threshold: i64, values: &mut Option<(i64, i64)>) -> Self {
+    /// Explodes the leftmost pair that is nested too deeply, pushing the steps to it onto
+    /// `path`, if given, from the pair up to the root.
+    fn explode(
+        self,
+        threshold: i64,
+        values: &mut Option<(i64, i64)>,
+        mut path: Option<&mut Vec<Side>>,
+    ) -> Self {
         return_if!(self.height() < threshold, self);
         match self {
-            this @ SfNumber::Single { .. } => this,
-            SfNumber::Pair {
+            this @ Node::Single { .. } => this,
+            Node::Pair {
                 height,
                 lhs,
                 mut rhs,
             } => {
                 if lhs.is_single() && rhs.is_single() {
                     *values = Some((lhs.magnitude(), rhs.magnitude()));
-                    SfNumber::new_single(0)
+                    Node::new_single(0)
                 } else {
-                    let mut lhs = lhs.explode(height - 1, values);
+                    let mut lhs = lhs.explode(height - 1, values, path.as_deref_mut());
                     return_if!(values.is_some(), {
+                        if let Some(path) = path {
+                            path.push(Side::Left);
+                        }
                         let (_, rv) = values.as_mut().unwrap();
                         rhs.accept_value_left(rv);
-                        SfNumber::new_pair(lhs, *rhs)
+                        Node::new_pair(lhs, *rhs)
                     });
 
-                    let rhs = rhs.explode(height - 1, values);
+                    let rhs = rhs.explode(height - 1, values, path.as_deref_mut());
                     return_if!(values.is_some(), {
+                        if let Some(path) = path {
+                            path.push(Side::Right);
+                        }
                         let (lv, _) = values.as_mut().unwrap();
                         lhs.accept_value_right(lv);
-                        SfNumber::new_pair(lhs, rhs)
+                        Node::new_pair(lhs, rhs)
                     });
 
-                    SfNumber::new_pair(lhs, rhs)
+                    Node::new_pair(lhs, rhs)
                 }
             }
         }
     }
 }
 
+impl Add for SfNumber {
+    type Output = SfNumber;
+
+    /// Pairs up two numbers and reduces the pair.
+    fn add(self, rhs: SfNumber) -> Self {
+        SfNumber(Node::new_pair(self.0, rhs.0).reduce())
+    }
+}
+
+impl Sum for SfNumber {
+    /// Adds up the numbers from left to right.
+    ///
+    /// Panics if there are no numbers, since snailfish addition has no identity.
+    fn sum<I: Iterator<Item = SfNumber>>(iter: I) -> Self {
+        SfNumber::try_sum(iter).expect("a sum of snailfish numbers needs at least one number")
+    }
+}
+
+//==================================================================================
+// Tracing
+//==================================================================================
+
+/// A step in the reduction of a snailfish number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Explode,
+    Split,
+}
+
+/// Which element of a pair to descend into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// An action taken while reducing a number, and the number before and after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    /// The way from the root to the pair that exploded or the number that split.
+    pub path: Vec<Side>,
+    pub before: String,
+    pub after: String,
+}
+
+/// Every action taken while adding two numbers.
+///
+/// Displays as the walkthrough in the puzzle's description:
+///
+/// ```text
+/// after addition: [[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]
+/// after explode:  [[[[0,7],4],[7,[[8,4],9]]],[1,1]]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    /// The sum before it was reduced.
+    pub sum: String,
+    pub steps: Vec<Step>,
+}
+
+impl SfNumber {
+    /// Adds two numbers like `+` does, recording each action of the reduction.
+    pub fn add_traced(self, rhs: SfNumber) -> (Self, Trace) {
+        let mut number = Node::new_pair(self.0, rhs.0);
+        let mut trace = Trace {
+            sum: number.to_string(),
+            steps: Vec::new(),
+        };
+        loop {
+            let mut path = Vec::new();
+            let before = number.to_string();
+            let (next, action) = number.step(Some(&mut path));
+            number = next;
+            let Some(action) = action else {
+                return (SfNumber(number), trace);
+            };
+            trace.steps.push(Step {
+                action,
+                path,
+                before,
+                after: number.to_string(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16}{}", "after addition:", self.sum)?;
+        for step in &self.steps {
+            let label = match step.action {
+                Action::Explode => "after explode:",
+                Action::Split => "after split:",
+            };
+            writeln!(f, "{:<16}{}", label, step.after)?;
+        }
+        Ok(())
+    }
+}
+
 #[aoc(day18, part1)]
 fn solve_part1(input: &Input) -> Output {
-    input
-        .iter()
-        .cloned()
-        .reduce(|lhs, rhs| lhs.add(rhs))
-        .unwrap()
-        .magnitude()
+    input.iter().cloned().sum::<SfNumber>().magnitude()
 }
 
 #[aoc(day18, part2)]
@@ -241,8 +459,8 @@ fn solve_part2(input: &Input) -> Output {
         .combinations(2)
         .map(|combo| {
             std::cmp::max(
-                combo[0].clone().add(combo[1].clone()).magnitude(),
-                combo[1].clone().add(combo[0].clone()).magnitude(),
+                (combo[0].clone() + combo[1].clone()).magnitude(),
+                (combo[1].clone() + combo[0].clone()).magnitude(),
             )
         })
         .max()
@@ -274,10 +492,83 @@ mod test {
         test_roundtrip("[[[[0,7],4],[15,[0,13]]],[1,1]]");
     }
 
+    fn test_parse_error(input: &str, expected: &str, offset: usize) {
+        let error = parse_sf_number(input.as_bytes()).unwrap_err();
+        assert_eq!(error.expected(), expected, "{}", input);
+        assert_eq!(error.offset(input.as_bytes()), offset, "{}", input);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        test_parse_error("", "a digit or '['", 0);
+        test_parse_error("[1;2]", "','", 2);
+        test_parse_error("[[1,2],x]", "a digit or '['", 7);
+        test_parse_error("[1,2", "']'", 4);
+    }
+
+    #[test]
+    fn test_generator_errors() {
+        let error = input_generator("[1,2]\n[[3,4],5]]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 10: expected end of input, found \"]\"",
+        );
+        let error = input_generator("[1,2]\n[3;4]").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+    }
+
+    #[test]
+    fn test_from_str() {
+        let number: SfNumber = "[[1,2],3]".parse().unwrap();
+        assert_eq!(
+            number,
+            SfNumber::new_pair(
+                SfNumber::new_pair(SfNumber::new_single(1), SfNumber::new_single(2)),
+                SfNumber::new_single(3),
+            )
+        );
+        let error = "[[1,2],3".parse::<SfNumber>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 9: expected ']', found the end of the line",
+        );
+        let error = "[1,2] ".parse::<SfNumber>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+    }
+
+    fn sf(input: &str) -> SfNumber {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_sum() {
+        let numbers = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]"].map(sf);
+        assert_eq!(
+            numbers.into_iter().sum::<SfNumber>(),
+            sf("[[[[3,0],[5,3]],[4,4]],[5,5]]"),
+        );
+        assert_eq!(sf("[1,1]") + sf("[2,2]"), sf("[[1,1],[2,2]]"));
+        assert_eq!(SfNumber::try_sum([]), None);
+        assert_eq!(SfNumber::try_sum([sf("[1,1]")]), Some(sf("[1,1]")));
+    }
+
+    #[test]
+    fn test_ordering() {
+        // Both have a magnitude of 27.
+        assert!(sf("[9,0]") < sf("[[1,2],3]"));
+        assert!(sf("[[1,2],3]") < sf("[[1,2],4]"));
+        assert!(sf("[9,1]") > sf("[1,9]"));
+        assert_eq!(sf("[1,9]").cmp(&sf("[1,9]")), Ordering::Equal);
+        // The split overstates the height of the outer pair, which equality ignores.
+        let mut split = sf("[[1,2],10]");
+        assert!(split.0.split(None));
+        assert_eq!(split, sf("[[1,2],[5,5]]"));
+    }
+
     fn test_explode(before: &str, after: &str) {
         let mut values = None;
         let number = parse_sf_number(before.as_bytes()).unwrap().0;
-        let exploded = number.explode(5, &mut values);
+        let exploded = number.explode(5, &mut values, None);
         assert!(dbg!(values).is_some());
         assert_eq!(exploded.to_string(), after);
     }
@@ -311,7 +602,7 @@ mod test {
 
     fn test_split(before: &str, after: &str) {
         let mut number = parse_sf_number(before.as_bytes()).unwrap().0;
-        assert!(number.split());
+        assert!(number.split(None));
         assert_eq!(number.to_string(), after, "\nbefore: {}", before,);
     }
 
@@ -355,7 +646,7 @@ mod test {
                 $($rhs),*
             ]
             .into_iter()
-            .map(|input| parse_sf_number(input.as_bytes()).unwrap().0)
+            .map(sf)
             .fold1(|lhs, rhs| lhs.add(rhs))
             .unwrap()
             .to_string();
@@ -464,6 +755,46 @@ mod test {
         );
     }
 
+    fn path(step: &Step) -> String {
+        step.path
+            .iter()
+            .map(|side| match side {
+                Side::Left => 'L',
+                Side::Right => 'R',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trace() {
+        let (sum, trace) = sf("[[[[4,3],4],4],[7,[[8,4],9]]]").add_traced(sf("[1,1]"));
+        assert_eq!(sum.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+        assert_eq!(
+            trace.to_string(),
+            "after addition: [[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]\n\
+             after explode:  [[[[0,7],4],[7,[[8,4],9]]],[1,1]]\n\
+             after explode:  [[[[0,7],4],[15,[0,13]]],[1,1]]\n\
+             after split:    [[[[0,7],4],[[7,8],[0,13]]],[1,1]]\n\
+             after split:    [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]\n\
+             after explode:  [[[[0,7],4],[[7,8],[6,0]]],[8,1]]\n",
+        );
+        let paths: Vec<_> = trace.steps.iter().map(path).collect();
+        assert_eq!(paths, ["LLLL", "LRRL", "LRL", "LRRR", "LRRR"]);
+        assert_eq!(trace.steps[0].before, trace.sum);
+        assert!(trace
+            .steps
+            .windows(2)
+            .all(|steps| steps[0].after == steps[1].before));
+    }
+
+    #[test]
+    fn test_trace_without_reduction() {
+        let (sum, trace) = sf("[1,2]").add_traced(sf("[[3,4],5]"));
+        assert_eq!(sum, sf("[[1,2],[[3,4],5]]"));
+        assert!(trace.steps.is_empty());
+        assert_eq!(trace.to_string(), "after addition: [[1,2],[[3,4],5]]\n");
+    }
+
     fn test_magnitude(expected: i64, input: &str) {
         assert_eq!(
             parse_sf_number(input.as_bytes())
@@ -0,0 +1,248 @@
+//! Snailfish numbers as a flat list of their regular numbers, each tagged with its depth.
+//!
+//! `[[1,2],3]` is stored as `1@2 2@2 3@1`: reading the regular numbers from left to right,
+//! with the count of pairs that enclose each one. A pair of regular numbers is then two
+//! neighbours of equal depth, so reduction is a walk along a vector rather than a descent
+//! through boxed nodes, and addition is a concatenation.
+
+use super::{Node, Output, SfNumber};
+use aoc_common::parse::ParseError;
+use aoc_runner_derive::*;
+use itertools::Itertools;
+use std::{fmt, ops::Add};
+
+type Input = Vec<FlatNumber>;
+
+/// The depth at which a pair explodes: nested inside four pairs.
+const EXPLODE_DEPTH: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Element {
+    depth: u8,
+    value: i64,
+}
+
+/// A snailfish number with the same addition, reduction and magnitude as [`SfNumber`].
+///
+/// Like the numbers in the puzzle, the numbers that are added must already be reduced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatNumber {
+    elements: Vec<Element>,
+}
+
+impl From<&SfNumber> for FlatNumber {
+    fn from(number: &SfNumber) -> Self {
+        fn flatten(number: &Node, depth: u8, elements: &mut Vec<Element>) {
+            match number {
+                Node::Single { value, .. } => elements.push(Element {
+                    depth,
+                    value: *value,
+                }),
+                Node::Pair { lhs, rhs, .. } => {
+                    flatten(lhs, depth + 1, elements);
+                    flatten(rhs, depth + 1, elements);
+                }
+            }
+        }
+        let mut elements = Vec::new();
+        flatten(&number.0, 0, &mut elements);
+        Self { elements }
+    }
+}
+
+impl fmt::Display for FlatNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn render(
+            elements: &[Element],
+            next: &mut usize,
+            depth: u8,
+            f: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            let element = elements[*next];
+            if element.depth == depth {
+                *next += 1;
+                return write!(f, "{}", element.value);
+            }
+            write!(f, "[")?;
+            render(elements, next, depth + 1, f)?;
+            write!(f, ",")?;
+            render(elements, next, depth + 1, f)?;
+            write!(f, "]")
+        }
+        render(&self.elements, &mut 0, 0, f)
+    }
+}
+
+impl FlatNumber {
+    pub fn magnitude(&self) -> i64 {
+        let mut stack: Vec<Element> = Vec::with_capacity(self.elements.len());
+        for &element in &self.elements {
+            let mut element = element;
+            while let Some(&lhs) = stack.last().filter(|lhs| lhs.depth == element.depth) {
+                stack.pop();
+                element = Element {
+                    depth: element.depth - 1,
+                    value: 3 * lhs.value + 2 * element.value,
+                };
+            }
+            stack.push(element);
+        }
+        stack[0].value
+    }
+
+    /// Explodes the pair whose left element is at `i`, returning the index of the zero
+    /// that replaces it.
+    fn explode(&mut self, i: usize) -> usize {
+        let (lhs, rhs) = (self.elements[i], self.elements[i + 1]);
+        if let Some(left) = i.checked_sub(1) {
+            self.elements[left].value += lhs.value;
+        }
+        if let Some(right) = self.elements.get_mut(i + 2) {
+            right.value += rhs.value;
+        }
+        self.elements[i] = Element {
+            depth: lhs.depth - 1,
+            value: 0,
+        };
+        self.elements.remove(i + 1);
+        i
+    }
+
+    /// Splits the regular number at `i` into a pair, returning whether the pair is deep
+    /// enough to explode.
+    fn split(&mut self, i: usize) -> bool {
+        let Element { depth, value } = self.elements[i];
+        let pair = [value / 2, value / 2 + value % 2].map(|value| Element {
+            depth: depth + 1,
+            value,
+        });
+        self.elements.splice(i..=i, pair);
+        depth + 1 >= EXPLODE_DEPTH
+    }
+
+    fn reduce(mut self) -> Self {
+        // Adding two reduced numbers nests no pair deeper than the explode depth, so every
+        // pair that is too deep can explode in a single pass from left to right.
+        let mut i = 0;
+        while i < self.elements.len() {
+            if self.elements[i].depth >= EXPLODE_DEPTH {
+                self.explode(i);
+            }
+            i += 1;
+        }
+
+        // After that, only a split makes a pair that is too deep, and it explodes before
+        // anything else splits. Its left value only grows the number before it, so the
+        // search for the next split resumes there.
+        let mut i = 0;
+        while i < self.elements.len() {
+            if self.elements[i].value < 10 {
+                i += 1;
+                continue;
+            }
+            if self.split(i) {
+                i = self.explode(i).saturating_sub(1);
+            }
+        }
+        self
+    }
+}
+
+impl Add for &FlatNumber {
+    type Output = FlatNumber;
+
+    fn add(self, rhs: &FlatNumber) -> FlatNumber {
+        let elements = self
+            .elements
+            .iter()
+            .chain(&rhs.elements)
+            .map(|element| Element {
+                depth: element.depth + 1,
+                ..*element
+            })
+            .collect();
+        FlatNumber { elements }.reduce()
+    }
+}
+
+#[aoc_generator(day18, part1, Flat)]
+#[aoc_generator(day18, part2, Flat)]
+fn input_generator_flat(raw_input: &str) -> Result<Input, ParseError> {
+    Ok(super::input_generator(raw_input)?
+        .iter()
+        .map(FlatNumber::from)
+        .collect())
+}
+
+#[aoc(day18, part1, Flat)]
+fn solve_part1_flat(input: &Input) -> Output {
+    input[1..]
+        .iter()
+        .fold(input[0].clone(), |sum, number| &sum + number)
+        .magnitude()
+}
+
+#[aoc(day18, part2, Flat)]
+fn solve_part2_flat(input: &Input) -> Output {
+    input
+        .iter()
+        .permutations(2)
+        .map(|pair| (pair[0] + pair[1]).magnitude())
+        .max()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn flat(input: &str) -> FlatNumber {
+        FlatNumber::from(&input.parse::<SfNumber>().unwrap())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in ["[1,2]", "[[1,2],3]", "[[[[0,7],4],[15,[0,13]]],[1,1]]"] {
+            assert_eq!(flat(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_addition() {
+        assert_eq!(
+            (&flat("[[[[4,3],4],4],[7,[[8,4],9]]]") + &flat("[1,1]")).to_string(),
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+        );
+        assert_eq!(
+            (&flat("[[[[6,6],[6,6]],[[6,0],[6,7]]],[[[7,7],[8,9]],[8,[8,1]]]]") + &flat("[2,9]"))
+                .to_string(),
+            "[[[[6,6],[7,7]],[[0,7],[7,7]]],[[[5,5],[5,6]],9]]",
+        );
+    }
+
+    #[test]
+    fn test_agrees_with_trees() {
+        let homework = [
+            "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]",
+            "[[[5,[2,8]],4],[5,[[9,9],0]]]",
+            "[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]",
+            "[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]",
+            "[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]",
+            "[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]",
+            "[[[[5,4],[7,7]],8],[[8,3],8]]",
+            "[[9,3],[[9,9],[6,[4,9]]]]",
+            "[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]",
+            "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]",
+        ];
+        let trees: Vec<SfNumber> = homework.iter().map(|n| n.parse().unwrap()).collect();
+        for (lhs, rhs) in trees.iter().tuple_combinations() {
+            let sum = lhs.clone() + rhs.clone();
+            let flat_sum = &FlatNumber::from(lhs) + &FlatNumber::from(rhs);
+            assert_eq!(flat_sum.to_string(), sum.to_string());
+            assert_eq!(flat_sum.magnitude(), sum.magnitude());
+        }
+        let flats: Vec<FlatNumber> = trees.iter().map(FlatNumber::from).collect();
+        assert_eq!(solve_part1_flat(&flats), 4140);
+        assert_eq!(solve_part2_flat(&flats), 3993);
+    }
+}
@@ -1,206 +1,584 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
-use flow_control::return_if;
 use hashbrown::{HashMap, HashSet};
 use itertools::Itertools;
-use text_io::scan;
+use std::{array, fmt::Write as _};
 
 type Input = Vec<Scanner>;
 type Output = usize;
 
+/// The count of beacons that two scanners must both detect for the puzzle to consider
+/// them overlapping.
+const OVERLAP: usize = 12;
+
+/// The beacons detected by a scanner, relative to the scanner and in its orientation.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Scanner {
-    data: HashSet<[i16; 3]>,
+pub struct Scanner<const N: usize = 3> {
+    beacons: HashSet<[i32; N]>,
+    /// How many pairs of beacons are each squared distance apart, which is the same in
+    /// every orientation and so can rule out an overlap without aligning the scanners.
+    fingerprint: HashMap<i64, usize>,
 }
 
 #[aoc_generator(day19)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    parse(raw_input)
+}
+
+/// Parses the reports of every scanner.
+pub fn parse(raw_input: &str) -> Result<Vec<Scanner>, ParseError> {
     raw_input
         .split("\n\n")
-        .map(|lines| Scanner {
-            data: lines
-                .lines()
-                .skip(1)
+        .map(|block| {
+            let mut lines = block.lines();
+            let header = lines
+                .next()
+                .ok_or_else(|| ParseError::new(raw_input, block, "a scanner header"))?;
+            let [number] = parse::scan(raw_input, header, "--- scanner {} ---")?;
+            parse::value::<usize>(raw_input, number, "a scanner number")?;
+            let beacons = lines
                 .map(|line| {
-                    let [x, y, z]: [i16; 3];
-                    scan!(line.bytes() => "{},{},{}", x, y, z);
-                    [x, y, z]
+                    let [x, y, z] = parse::scan(raw_input, line, "{},{},{}")?
+                        .map(|n| parse::value(raw_input, n, "a coordinate"));
+                    Ok([x?, y?, z?])
                 })
-                .collect::<HashSet<_>>(),
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Scanner::new(beacons))
         })
         .collect()
 }
 
-#[rustfmt::skip]
-const TRANSFORMS: [fn(&[i16; 3]) -> [i16; 3]; 24] = [
-    |&[x, y, z]| [ x, -y, -z],
-    |&[x, y, z]| [ x,  z,  y],
-    |&[x, y, z]| [ x,  z, -y],
-    |&[x, y, z]| [ x, -z,  y],
+/// Shifts the first-argument point by the second-argument distance.
+fn shift<const N: usize>(point: [i32; N], distance: [i32; N]) -> [i32; N] {
+    array::from_fn(|axis| point[axis] + distance[axis])
+}
 
-    |&[x, y, z]| [-x,  y, -z],
-    |&[x, y, z]| [-x, -y,  z],
-    |&[x, y, z]| [-x,  z,  y],
-    |&[x, y, z]| [-x, -z, -y],
+/// Returns the distance that the second-argument point would travel to reach the first.
+fn difference<const N: usize>(to: [i32; N], from: [i32; N]) -> [i32; N] {
+    array::from_fn(|axis| to[axis] - from[axis])
+}
 
-    |&[x, y, z]| [ y,  x, -z],
-    |&[x, y, z]| [ y, -x,  z],
-    |&[x, y, z]| [ y,  z,  x],
-    |&[x, y, z]| [ y, -z, -x],
+/// Returns the manhattan distance between two points.
+fn manhattan_distance<const N: usize>(a: [i32; N], b: [i32; N]) -> i32 {
+    difference(a, b).iter().map(|d| d.abs()).sum()
+}
 
-    |&[x, y, z]| [-y,  x,  z],
-    |&[x, y, z]| [-y, -x, -z],
-    |&[x, y, z]| [-y,  z, -x],
-    |&[x, y, z]| [-y, -z,  x],
+/// Returns the square of the euclidean distance between two points.
+fn squared_distance<const N: usize>(a: [i32; N], b: [i32; N]) -> i64 {
+    difference(a, b).iter().map(|&d| (d as i64).pow(2)).sum()
+}
 
-    |&[x, y, z]| [ z,  x,  y],
-    |&[x, y, z]| [ z, -x, -y],
-    |&[x, y, z]| [ z,  y, -x],
-    |&[x, y, z]| [ z, -y,  x],
+//==================================================================================
+// Rotations
+//==================================================================================
 
-    |&[x, y, z]| [-z,  x, -y],
-    |&[x, y, z]| [-z, -x,  y],
-    |&[x, y, z]| [-z,  y,  x],
-    |&[x, y, z]| [-z, -y, -x],
-];
+/// A rotation that maps each axis onto an axis, such as a quarter turn about the z axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation<const N: usize> {
+    /// The axis of the original point that each axis of the rotated point is taken from.
+    axes: [usize; N],
+    /// Whether each axis of the rotated point is negated, as 1 or -1.
+    signs: [i32; N],
+}
 
-/// Shifts the first-argument point by the second-argument distance.
-fn shift([a1, b1, c1]: [i16; 3], [a2, b2, c2]: [i16; 3]) -> [i16; 3] {
-    [a1 + a2, b1 + b2, c1 + c2]
+impl<const N: usize> Rotation<N> {
+    pub fn identity() -> Self {
+        Self {
+            axes: array::from_fn(|axis| axis),
+            signs: [1; N],
+        }
+    }
+
+    /// Returns every rotation of N-dimensional space that maps axes onto axes, starting
+    /// with the identity: 24 in three dimensions.
+    ///
+    /// These are the permutations of the axes with any of them negated, except for those
+    /// that mirror space: the ones with an odd count of swaps and negations combined.
+    pub fn all() -> Vec<Self> {
+        let mut rotations = Vec::new();
+        for permutation in (0..N).permutations(N) {
+            let inversions = permutation
+                .iter()
+                .tuple_combinations()
+                .filter(|(a, b)| a > b)
+                .count();
+            for negations in 0..1_u32 << N {
+                if (inversions + negations.count_ones() as usize).is_multiple_of(2) {
+                    rotations.push(Self {
+                        axes: array::from_fn(|axis| permutation[axis]),
+                        signs: array::from_fn(|axis| 1 - 2 * (negations >> axis & 1) as i32),
+                    });
+                }
+            }
+        }
+        rotations
+    }
+
+    pub fn apply(&self, point: [i32; N]) -> [i32; N] {
+        array::from_fn(|axis| self.signs[axis] * point[self.axes[axis]])
+    }
+
+    /// Returns the rotation that applies `inner` and then this rotation.
+    pub fn after(&self, inner: &Self) -> Self {
+        Self {
+            axes: array::from_fn(|axis| inner.axes[self.axes[axis]]),
+            signs: array::from_fn(|axis| self.signs[axis] * inner.signs[self.axes[axis]]),
+        }
+    }
 }
 
-/// Returns the manhattan distance between two points.
-fn manhattan_distance([a1, b1, c1]: [i16; 3], [a2, b2, c2]: [i16; 3]) -> i16 {
-    (a1 - a2).abs() + (b1 - b2).abs() + (c1 - c2).abs()
+/// How to take a scanner's readings into another scanner's frame: rotate them, then
+/// shift them by the position of the scanner in that frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alignment<const N: usize> {
+    pub rotation: Rotation<N>,
+    pub position: [i32; N],
+}
+
+impl<const N: usize> Alignment<N> {
+    pub fn identity() -> Self {
+        Self {
+            rotation: Rotation::identity(),
+            position: [0; N],
+        }
+    }
+
+    pub fn apply(&self, point: [i32; N]) -> [i32; N] {
+        shift(self.rotation.apply(point), self.position)
+    }
+
+    /// Returns the alignment that applies `inner` and then this alignment.
+    pub fn after(&self, inner: &Self) -> Self {
+        Self {
+            rotation: self.rotation.after(&inner.rotation),
+            position: self.apply(inner.position),
+        }
+    }
 }
 
-impl Scanner {
-    /// Returns an iterator over the scanner's points in every possible orientation.
-    fn all_orientations(&self) -> impl Iterator<Item = impl Iterator<Item = [i16; 3]> + '_> + '_ {
-        TRANSFORMS
+//==================================================================================
+// Alignment
+//==================================================================================
+
+impl<const N: usize> Scanner<N> {
+    pub fn new(beacons: impl IntoIterator<Item = [i32; N]>) -> Self {
+        let beacons: HashSet<_> = beacons.into_iter().collect();
+        let mut fingerprint = HashMap::new();
+        for (&a, &b) in beacons.iter().tuple_combinations() {
+            *fingerprint.entry(squared_distance(a, b)).or_default() += 1;
+        }
+        Self {
+            beacons,
+            fingerprint,
+        }
+    }
+
+    /// Returns whether the scanners have enough pairs of beacons the same distance apart
+    /// to possibly share `threshold` beacons, which would make for that many pairs.
+    fn could_overlap(&self, scanner: &Self, threshold: usize) -> bool {
+        let shared_pairs: usize = self
+            .fingerprint
             .iter()
-            .map(|transform| self.data.iter().map(transform))
+            .map(|(distance, &count)| count.min(*scanner.fingerprint.get(distance).unwrap_or(&0)))
+            .sum();
+        shared_pairs >= threshold * threshold.saturating_sub(1) / 2
     }
 
-    /// Returns the distances from each beacon detected by the scanner to this given point.
-    fn distances(&self, [x2, y2, z2]: [i16; 3]) -> impl Iterator<Item = [i16; 3]> + '_ {
-        self.data
+    /// Returns how many of scanner's beacons are also self's, once they are aligned.
+    fn shared_beacons(&self, scanner: &Self, alignment: &Alignment<N>) -> usize {
+        scanner
+            .beacons
             .iter()
-            .copied()
-            .map(move |[x1, y1, z1]| [(x1 - x2), (y1 - y2), (z1 - z2)])
+            .filter(|&&beacon| self.beacons.contains(&alignment.apply(beacon)))
+            .count()
     }
 
-    /// Returns the TRANSFORMS index that would align scanner to self's orientation,
-    /// as well as the distance in that orientation that scanner would have to travel to get to self.
+    /// Returns how to take scanner's readings into self's frame, if there is a rotation
+    /// among `rotations` in which at least `threshold` of scanner's beacons line up with
+    /// self's.
     ///
-    /// The THRESHOLD refers to the count of distances among the groups of points that are found to be the same.
-    /// If the count is at or above the THRESHOLD, then we found a matching orientation with enough shared points to
-    /// determine the distance between the scanners in that orientation.
-    fn distance_from<const THRESHOLD: usize>(&self, scanner: &Self) -> Option<(usize, [i16; 3])> {
-        for (transform_idx, orientation) in scanner.all_orientations().enumerate() {
-            let mut distances: HashMap<[i16; 3], usize> =
-                HashMap::with_capacity(self.data.len().pow(2));
-            for point in orientation {
-                for distance in self.distances(point) {
-                    *distances.entry(distance).or_default() += 1;
+    /// Each pairing of one of self's beacons with one of scanner's rotated beacons votes
+    /// for the distance between them as the position of scanner. A distance that gets
+    /// `threshold` votes is where scanner must be for that many beacons to line up.
+    pub fn align(
+        &self,
+        scanner: &Self,
+        rotations: &[Rotation<N>],
+        threshold: usize,
+    ) -> Option<Alignment<N>> {
+        if !self.could_overlap(scanner, threshold) {
+            return None;
+        }
+        let mut votes: HashMap<[i32; N], usize> =
+            HashMap::with_capacity(self.beacons.len() * scanner.beacons.len());
+        for rotation in rotations {
+            votes.clear();
+            for point in scanner.beacons.iter().map(|&point| rotation.apply(point)) {
+                for &beacon in &self.beacons {
+                    let position = difference(beacon, point);
+                    let count = votes.entry(position).or_default();
+                    *count += 1;
+                    if *count >= threshold {
+                        return Some(Alignment {
+                            rotation: *rotation,
+                            position,
+                        });
+                    }
                 }
             }
-            let found = distances.into_iter().find_map(|(distance, count)| {
-                (count >= THRESHOLD).then(|| (transform_idx, distance))
-            });
-            return_if!(found.is_some(), found);
         }
         None
     }
+}
 
-    /// Returns self as transformed by TRANSFORMS[transform_idx] and moved by distance
-    /// in the new orientation.
-    fn transmoved_by(&self, transform_idx: usize, distance: [i16; 3]) -> Self {
-        Self {
-            data: self
-                .data
-                .iter()
-                .map(|point| shift(TRANSFORMS[transform_idx](point), distance))
-                .collect(),
+/// The alignment of a scanner against one that was already aligned: an edge of the
+/// graph of alignments, which is a tree rooted at the first scanner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub reference: usize,
+    pub scanner: usize,
+    /// How many beacons both scanners detect.
+    pub shared: usize,
+    /// The index in [`Rotation::all`] of the rotation that turns scanner's readings to
+    /// reference's orientation.
+    pub rotation: usize,
+}
+
+/// Every beacon in the frame of the first scanner, along with where each scanner is in
+/// that frame, how it is turned, and which scanner it was aligned against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BeaconMap<const N: usize> {
+    pub beacons: HashSet<[i32; N]>,
+    /// How to take each scanner's readings into the first scanner's frame, or `None` for a
+    /// scanner that could not be aligned with any other.
+    pub alignments: Vec<Option<Alignment<N>>>,
+    /// The alignments between pairs of scanners, in the order that they were found.
+    pub edges: Vec<Edge>,
+}
+
+impl<const N: usize> BeaconMap<N> {
+    /// Returns the scanners that could not be aligned with any other.
+    pub fn unaligned(&self) -> Vec<usize> {
+        (0..self.alignments.len())
+            .filter(|&i| self.alignments[i].is_none())
+            .collect()
+    }
+
+    /// Returns where a scanner is and the index of its rotation, relative to the first.
+    fn placement(&self, i: usize) -> Option<([i32; N], usize)> {
+        let alignment = self.alignments[i]?;
+        let rotations = Rotation::all();
+        let rotation = rotations.iter().position(|r| *r == alignment.rotation)?;
+        Some((alignment.position, rotation))
+    }
+
+    /// Describes how each scanner was aligned, or that it could not be.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        for i in 0..self.alignments.len() {
+            let Some((position, rotation)) = self.placement(i) else {
+                writeln!(report, "scanner {} was not aligned with any other", i).unwrap();
+                continue;
+            };
+            write!(
+                report,
+                "scanner {} at {:?}, rotation {}",
+                i, position, rotation
+            )
+            .unwrap();
+            if let Some(edge) = self.edges.iter().find(|edge| edge.scanner == i) {
+                write!(
+                    report,
+                    ", aligned against scanner {} with {} shared beacons",
+                    edge.reference, edge.shared,
+                )
+                .unwrap();
+            }
+            report.push('\n');
         }
+        let unaligned = self.unaligned();
+        writeln!(
+            report,
+            "{} of {} scanners aligned, {} beacons",
+            self.alignments.len() - unaligned.len(),
+            self.alignments.len(),
+            self.beacons.len(),
+        )
+        .unwrap();
+        report
     }
 
-    /// Shifts scanner to self's perspective, if possible, and adds the coordinates
-    /// of all of scanner's known beacon's to self's beacon set in self's perspective.
-    ///
-    /// Returns the number of scanner's beacons that were added to self's beacon set.
-    fn gain_perspective<const THRESHOLD: usize>(&mut self, scanner: &Self) -> usize {
-        let start_len = self.data.len();
-        if let Some((transform_idx, distance)) = self.distance_from::<THRESHOLD>(scanner) {
-            for point in scanner.transmoved_by(transform_idx, distance).data {
-                self.data.insert(point);
+    /// Renders the graph of alignments as a Graphviz digraph, from each scanner to the
+    /// scanners that were aligned against it. Scanners that were not aligned are dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph scanners {\n    node [shape=box];\n");
+        for i in 0..self.alignments.len() {
+            match self.placement(i) {
+                Some((position, _)) => {
+                    writeln!(
+                        dot,
+                        "    s{} [label=\"scanner {}\\n{:?}\"];",
+                        i, i, position
+                    )
+                }
+                None => writeln!(
+                    dot,
+                    "    s{} [label=\"scanner {}\\nnot aligned\", style=dashed];",
+                    i, i,
+                ),
             }
+            .unwrap();
+        }
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "    s{} -> s{} [label=\"{} shared\\nrotation {}\"];",
+                edge.reference, edge.scanner, edge.shared, edge.rotation,
+            )
+            .unwrap();
         }
-        self.data.len() - start_len
+        dot.push_str("}\n");
+        dot
     }
 }
 
-/// Merges all the scanners into one scanner that knows the location of every beacon
-/// in a single orientation.
-fn merge_all_perspectives(mut scanners: Input) -> Scanner {
-    let mut scanner = scanners.swap_remove(0);
-    while !scanners.is_empty() {
+/// Aligns every scanner that it can with the first scanner, by way of the scanners that
+/// are already aligned, and merges the beacons that they detect into a single map.
+///
+/// Scanners are aligned when they share at least `threshold` beacons. Those that share too
+/// few with every aligned scanner are left out of the map, and reported as unaligned.
+pub fn merge_all_perspectives<const N: usize>(
+    scanners: &[Scanner<N>],
+    threshold: usize,
+) -> BeaconMap<N> {
+    let rotations = Rotation::all();
+    let mut alignments = vec![None; scanners.len()];
+    let mut aligned = Vec::new();
+    let mut edges = Vec::new();
+    if !scanners.is_empty() {
+        alignments[0] = Some(Alignment::identity());
+        aligned.push(0);
+    }
+    while let Some(reference) = aligned.pop() {
         for i in 0..scanners.len() {
-            if scanner.gain_perspective::<12>(&scanners[i]) > 0 {
-                scanners.swap_remove(i);
-                break;
+            if alignments[i].is_some() {
+                continue;
             }
+            if let Some(alignment) = scanners[reference].align(&scanners[i], &rotations, threshold)
+            {
+                alignments[i] = alignments[reference].map(|outer| outer.after(&alignment));
+                aligned.push(i);
+                edges.push(Edge {
+                    reference,
+                    scanner: i,
+                    shared: scanners[reference].shared_beacons(&scanners[i], &alignment),
+                    rotation: rotations
+                        .iter()
+                        .position(|rotation| *rotation == alignment.rotation)
+                        .unwrap(),
+                });
+            }
+        }
+    }
+
+    let mut beacons = HashSet::new();
+    for (scanner, alignment) in scanners.iter().zip(&alignments) {
+        if let Some(alignment) = alignment {
+            beacons.extend(
+                scanner
+                    .beacons
+                    .iter()
+                    .map(|&beacon| alignment.apply(beacon)),
+            );
         }
     }
-    scanner
+    BeaconMap {
+        beacons,
+        alignments,
+        edges,
+    }
 }
 
 #[aoc(day19, part1)]
 fn solve_part1(scanners: &Input) -> Output {
-    let the_one_scanner_to_rule_them_all = merge_all_perspectives(scanners.clone());
-    the_one_scanner_to_rule_them_all.data.len()
+    merge_all_perspectives(scanners, OVERLAP).beacons.len()
 }
 
 #[aoc(day19, part2)]
 fn solve_part2(scanners: &Input) -> Output {
-    let the_one_scanner_to_rule_them_all = merge_all_perspectives(scanners.clone());
-    scanners
+    merge_all_perspectives(scanners, OVERLAP)
+        .alignments
         .iter()
-        .filter_map(|scanner| the_one_scanner_to_rule_them_all.distance_from::<12>(scanner))
-        .map(|(_, location)| location)
-        .combinations(2)
-        .map(|combo| manhattan_distance(combo[0], combo[1]))
+        .flatten()
+        .map(|alignment| alignment.position)
+        .tuple_combinations()
+        .map(|(a, b)| manhattan_distance(a, b))
         .max()
-        .unwrap() as usize
+        .unwrap_or(0) as usize
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
 
     #[test]
-    fn transforms_are_unique() {
-        let set = std::iter::repeat([1, 2, 3])
-            .zip(TRANSFORMS)
-            .map(|(point, transform)| transform(&point))
+    fn rotations_are_unique() {
+        let rotations = Rotation::<3>::all();
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(rotations[0], Rotation::identity());
+        let set = rotations
+            .iter()
+            .map(|rotation| rotation.apply([1, 2, 3]))
             .collect::<HashSet<_>>();
-        assert_eq!(set.len(), TRANSFORMS.len());
+        assert_eq!(set.len(), rotations.len());
+        // Swapping two axes mirrors space, unless one of them is negated.
+        assert!(!set.contains(&[1, 3, 2]));
+        assert!(set.contains(&[1, 3, -2]));
+
+        assert_eq!(Rotation::<2>::all().len(), 4);
+        assert_eq!(Rotation::<4>::all().len(), 192);
+    }
+
+    #[test]
+    fn rotations_form_a_group() {
+        let rotations = Rotation::<3>::all();
+        let set: HashSet<_> = rotations.iter().collect();
+        for (a, b) in rotations.iter().cartesian_product(&rotations) {
+            let composed = a.after(b);
+            assert!(set.contains(&composed));
+            assert_eq!(composed.apply([1, 2, 3]), a.apply(b.apply([1, 2, 3])));
+        }
+    }
+
+    #[test]
+    fn align() {
+        let scanner1 = Scanner::new([[0, 2], [4, 1], [3, 3]]);
+        let scanner2 = Scanner::new([[-1, -1], [-5, 0], [-2, 1]]);
+        let rotations = Rotation::all();
+
+        let alignment = scanner1.align(&scanner2, &rotations, 3).unwrap();
+        assert_eq!(alignment.rotation, Rotation::identity());
+        assert_eq!(alignment.position, [5, 2]);
+        let aligned: HashSet<_> = scanner2
+            .beacons
+            .iter()
+            .map(|&beacon| alignment.apply(beacon))
+            .collect();
+        assert_eq!(aligned, scanner1.beacons);
+
+        assert_eq!(scanner1.shared_beacons(&scanner2, &alignment), 3);
+        assert_eq!(scanner1.align(&scanner2, &rotations, 4), None);
+        let elsewhere = Scanner::new([[0, 0], [10, 0], [0, 30]]);
+        assert!(!scanner1.could_overlap(&elsewhere, 3));
+    }
+
+    /// Scatters beacons through a cube of space, and returns scanners that each detect
+    /// the beacons near them from a random position and rotation.
+    fn survey(rng: &mut StdRng, positions: &[[i32; 3]]) -> (Vec<Scanner>, Vec<Alignment<3>>) {
+        let beacons: Vec<[i32; 3]> = (0..400)
+            .map(|_| array::from_fn(|_| rng.gen_range(-1500..=1500)))
+            .collect();
+        let rotations = Rotation::all();
+        let mut scanners = Vec::new();
+        let mut alignments = Vec::new();
+        for (i, &position) in positions.iter().enumerate() {
+            let rotation = match i {
+                0 => Rotation::identity(),
+                _ => rotations[rng.gen_range(0..rotations.len())],
+            };
+            // The rotation that undoes this one.
+            let inverse = *rotations
+                .iter()
+                .find(|inverse| rotation.after(inverse) == Rotation::identity())
+                .unwrap();
+            let detected = beacons
+                .iter()
+                .map(|&beacon| difference(beacon, position))
+                .filter(|offset| offset.iter().all(|d| d.abs() <= 1000))
+                .map(|offset| inverse.apply(offset));
+            scanners.push(Scanner::new(detected));
+            alignments.push(Alignment { rotation, position });
+        }
+        (scanners, alignments)
+    }
+
+    #[test]
+    fn recovers_positions_and_rotations() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let positions = [[0, 0, 0], [900, 100, 0], [1100, -800, 300], [-700, 0, 800]];
+        let (scanners, expected) = survey(&mut rng, &positions);
+
+        let map = merge_all_perspectives(&scanners, OVERLAP);
+        let alignments: Vec<_> = map.alignments.iter().map(|a| a.unwrap()).collect();
+        assert_eq!(alignments, expected);
+        assert_eq!(map.edges.len(), scanners.len() - 1);
+        for edge in &map.edges {
+            assert!(edge.shared >= OVERLAP);
+            let reference = alignments[edge.reference];
+            let rotation = Rotation::all()[edge.rotation];
+            assert_eq!(
+                reference.rotation.after(&rotation),
+                alignments[edge.scanner].rotation,
+            );
+        }
+        assert!(map.unaligned().is_empty());
+        for (scanner, alignment) in scanners.iter().zip(&alignments) {
+            for &beacon in &scanner.beacons {
+                assert!(map.beacons.contains(&alignment.apply(beacon)));
+            }
+        }
+        // The third and fourth scanners are the furthest apart.
+        assert_eq!(solve_part2(&scanners), 1800 + 800 + 500);
+    }
+
+    #[test]
+    fn leaves_lone_scanners_unaligned() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let (scanners, _) = survey(&mut rng, &[[0, 0, 0], [600, 0, 0], [-5000, 0, 0]]);
+        let map = merge_all_perspectives(&scanners, OVERLAP);
+        assert!(map.alignments[1].is_some());
+        assert_eq!(map.alignments[2], None);
+        assert_eq!(map.unaligned(), [2]);
+        assert!(map
+            .report()
+            .contains("scanner 2 was not aligned with any other\n2 of 3 scanners aligned"));
     }
 
     #[test]
-    fn gain_perspective() {
-        let scanner1 = Scanner {
-            data: [[0, 2, 0], [4, 1, 0], [3, 3, 0]].into_iter().collect(),
-        };
-        let scanner2 = Scanner {
-            data: [[-1, -1, 0], [-5, 0, 0], [-2, 1, 0]].into_iter().collect(),
-        };
-
-        let mut s1 = scanner1.clone();
-        s1.gain_perspective::<3>(&scanner2);
-        assert_eq!(s1, scanner1);
-
-        let mut s2 = scanner2.clone();
-        s2.gain_perspective::<3>(&scanner1);
-        assert_eq!(s2, scanner2);
+    fn exports_the_alignment_graph() {
+        let scanners = [
+            Scanner::new([[0, 2], [4, 1], [3, 3]]),
+            Scanner::new([[-1, -1], [-5, 0], [-2, 1]]),
+            Scanner::new([[100, 100]]),
+        ];
+        let map = merge_all_perspectives(&scanners, 3);
+        assert_eq!(
+            map.edges,
+            [Edge {
+                reference: 0,
+                scanner: 1,
+                shared: 3,
+                rotation: 0,
+            }],
+        );
+        assert_eq!(
+            map.to_dot(),
+            "digraph scanners {
+    node [shape=box];
+    s0 [label=\"scanner 0\\n[0, 0]\"];
+    s1 [label=\"scanner 1\\n[5, 2]\"];
+    s2 [label=\"scanner 2\\nnot aligned\", style=dashed];
+    s0 -> s1 [label=\"3 shared\\nrotation 0\"];
+}
+",
+        );
+        assert_eq!(
+            map.report(),
+            "scanner 0 at [0, 0], rotation 0\n\
+             scanner 1 at [5, 2], rotation 0, aligned against scanner 0 with 3 shared beacons\n\
+             scanner 2 was not aligned with any other\n\
+             2 of 3 scanners aligned, 3 beacons\n",
+        );
     }
 }
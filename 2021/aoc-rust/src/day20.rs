@@ -1,37 +1,42 @@
+use aoc_common::{grid::rectangle, parse::ParseError};
 use aoc_runner_derive::*;
 use hashbrown::HashMap;
 
 type Input = (i64, Vec<i64>, HashMap<(i64, i64), i64>);
 type Output = usize;
 
+/// Parses `line`, which must be a slice of `raw_input`, as a row of dark and light pixels.
+fn pixels(raw_input: &str, line: &str) -> Result<Vec<i64>, ParseError> {
+    line.char_indices()
+        .map(|(index, c)| match c {
+            '.' => Ok(0),
+            '#' => Ok(1),
+            _ => Err(ParseError::new(raw_input, &line[index..], "'.' or '#'")),
+        })
+        .collect()
+}
+
 #[aoc_generator(day20, part1, nordzilla)]
 #[aoc_generator(day20, part2, nordzilla)]
-fn input_generator(raw_input: &str) -> Input {
-    let mut lines = raw_input.lines();
-    let line1 = lines.next().unwrap();
-    let enhanced_pixels = line1
-        .bytes()
-        .map(|byte| if byte == b'.' { 0 } else { 1 })
-        .collect::<Vec<_>>();
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    let (algorithm, raw_image) = raw_input
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::end_of_input(raw_input, "a blank line before the image"))?;
+    let enhanced_pixels = pixels(raw_input, algorithm)?;
+    if enhanced_pixels.len() != 512 {
+        return Err(ParseError::new(
+            raw_input,
+            algorithm,
+            "an enhancement algorithm of 512 pixels",
+        ));
+    }
     let mut image = HashMap::new();
-    lines.skip(1).enumerate().for_each(|(row, line)| {
-        line.bytes().enumerate().for_each(|(col, byte)| {
-            if byte == b'.' {
-                image.insert((row as i64, col as i64), 0);
-            } else {
-                image.insert((row as i64, col as i64), 1);
-            }
-        });
-    });
-    (enhanced_pixels[0] + 1, enhanced_pixels, image)
-}
-
-fn rectangle(
-    (start_row, start_col): (i64, i64),
-    rows: i64,
-    cols: i64,
-) -> impl Iterator<Item = (i64, i64)> {
-    (0..rows).flat_map(move |row| (0..cols).map(move |col| (start_row + row, start_col + col)))
+    for (row, line) in raw_image.lines().enumerate() {
+        for (col, pixel) in pixels(raw_input, line)?.into_iter().enumerate() {
+            image.insert((row as i64, col as i64), pixel);
+        }
+    }
+    Ok((enhanced_pixels[0] + 1, enhanced_pixels, image))
 }
 
 fn build_index(row: i64, col: i64, image: &HashMap<(i64, i64), i64>, oob: i64) -> usize {
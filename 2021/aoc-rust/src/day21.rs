@@ -1,21 +1,27 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use cached::proc_macro::cached;
 use flow_control::return_if;
-use text_io::scan;
 
 type Input = (usize, usize);
 type Output = usize;
 
 #[aoc_generator(day21, part1, nordzilla)]
 #[aoc_generator(day21, part2, nordzilla)]
-fn input_generator(raw_input: &str) -> (usize, usize) {
-    let mut lines = raw_input.lines();
-    let line1 = lines.next().unwrap();
-    let line2 = lines.next().unwrap();
-    let [pos1, pos2]: [usize; 2];
-    scan!(line1.bytes() => "Player 1 starting position: {}", pos1);
-    scan!(line2.bytes() => "Player 2 starting position: {}", pos2);
-    (pos1 - 1, pos2 - 1)
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    let [pos1, pos2] = parse::scan(
+        raw_input,
+        raw_input,
+        "Player 1 starting position: {}\nPlayer 2 starting position: {}",
+    )?
+    .map(|text| {
+        const EXPECTED: &str = "a position from 1 to 10";
+        match parse::value(raw_input, text, EXPECTED)? {
+            position @ 1..=10 => Ok(position - 1),
+            _ => Err(ParseError::new(raw_input, text, EXPECTED)),
+        }
+    });
+    Ok((pos1?, pos2?))
 }
 
 type State = [usize; 3];
@@ -1,6 +1,6 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use flow_control::return_if;
-use text_io::scan;
 
 type Input = Vec<Cuboid>;
 type Output = i64;
@@ -88,51 +88,40 @@ impl Cuboid {
     }
 }
 
+/// Parses a reboot step, returning its cuboid along with the cuboid's bounds.
+fn parse_step(raw_input: &str, line: &str) -> Result<(Cuboid, [i64; 6]), ParseError> {
+    let [onoff, x1, x2, y1, y2, z1, z2] =
+        parse::scan(raw_input, line, "{} x={}..{},y={}..{},z={}..{}")?;
+    let mut bounds = [0; 6];
+    for (bound, text) in bounds.iter_mut().zip([x1, x2, y1, y2, z1, z2]) {
+        *bound = parse::value(raw_input, text, "a coordinate")?;
+    }
+    match onoff {
+        "on" => Ok((Cuboid::On(CubeRange::new(bounds)), bounds)),
+        "off" => Ok((Cuboid::Off(CubeRange::new(bounds)), bounds)),
+        _ => Err(ParseError::new(raw_input, onoff, "on or off")),
+    }
+}
+
 #[aoc_generator(day22, part1, nordzilla)]
-fn input_generator1(raw_input: &str) -> Input {
+fn input_generator1(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .filter_map(|line| {
-            let onoff: String;
-            let [x1, x2, y1, y2, z1, z2]: [i64; 6];
-            scan!(line.bytes() => "{} x={}..{},y={}..{},z={}..{}", onoff, x1, x2, y1, y2, z1, z2);
-            (-50 <= x1
-                && x1 <= 50
-                && -50 <= x2
-                && x2 <= 50
-                && -50 <= y1
-                && y1 <= 50
-                && -50 <= y2
-                && y2 <= 50
-                && -50 <= z1
-                && z1 <= 50
-                && -50 <= z2
-                && z2 <= 50)
-                .then(|| {
-                    if "on" == onoff {
-                        Cuboid::On(CubeRange::new([x1, x2, y1, y2, z1, z2]))
-                    } else {
-                        Cuboid::Off(CubeRange::new([x1, x2, y1, y2, z1, z2]))
-                    }
-                })
+        .filter_map(|line| match parse_step(raw_input, line) {
+            Ok((cuboid, bounds)) => bounds
+                .iter()
+                .all(|bound| (-50..=50).contains(bound))
+                .then_some(Ok(cuboid)),
+            Err(e) => Some(Err(e)),
         })
         .collect()
 }
 
 #[aoc_generator(day22, part2, nordzilla)]
-fn input_generator2(raw_input: &str) -> Input {
+fn input_generator2(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .map(|line| {
-            let onoff: String;
-            let [x1, x2, y1, y2, z1, z2]: [i64; 6];
-            scan!(line.bytes() => "{} x={}..{},y={}..{},z={}..{}", onoff, x1, x2, y1, y2, z1, z2);
-            if "on" == onoff {
-                Cuboid::On(CubeRange::new([x1, x2, y1, y2, z1, z2]))
-            } else {
-                Cuboid::Off(CubeRange::new([x1, x2, y1, y2, z1, z2]))
-            }
-        })
+        .map(|line| parse_step(raw_input, line).map(|(cuboid, _)| cuboid))
         .collect()
 }
 
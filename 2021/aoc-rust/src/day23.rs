@@ -1,7 +1,7 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 use flow_control::return_if;
 use rayon::prelude::*;
-use text_io::scan;
 
 type Input1 = [Room<2>; 4];
 type Input2 = [Room<4>; 4];
@@ -13,42 +13,50 @@ struct Room<const N: usize> {
     occupants: [u8; N],
 }
 
+/// Parses the amphipods at the top and bottom of each room, from left to right.
+fn parse_rooms(raw_input: &str) -> Result<[[u8; 2]; 4], ParseError> {
+    let mut lines = raw_input.lines().skip(2);
+    let mut parse_row = |pattern| {
+        let line = lines
+            .next()
+            .ok_or_else(|| ParseError::end_of_input(raw_input, "a row of amphipods"))?;
+        let texts = parse::scan::<4>(raw_input, line, pattern)?;
+        let mut row = [0; 4];
+        for (amphipod, text) in row.iter_mut().zip(texts) {
+            *amphipod = match text {
+                "A" | "B" | "C" | "D" => text.as_bytes()[0],
+                _ => return Err(ParseError::new(raw_input, text, "an amphipod from A to D")),
+            };
+        }
+        Ok(row)
+    };
+    let top = parse_row("###{}#{}#{}#{}###")?;
+    let bottom = parse_row("  #{}#{}#{}#{}#")?;
+    Ok([0, 1, 2, 3].map(|room| [top[room], bottom[room]]))
+}
+
 #[aoc_generator(day23 part1, nordzilla)]
-fn input_generator1(raw_input: &str) -> Input1 {
-    let mut lines = raw_input.lines();
-    lines.next();
-    lines.next();
-
-    let [a20, a40, a60, a80]: [char; 4];
-    let [a21, a41, a61, a81]: [char; 4];
-    scan!(lines.next().unwrap().bytes() => "###{}#{}#{}#{}###", a20, a40, a60, a80);
-    scan!(lines.next().unwrap().bytes() => "  #{}#{}#{}#{}#", a21, a41, a61, a81);
-
-    let room2 = Room::<2>::new(2, [a20 as u8, a21 as u8]);
-    let room4 = Room::<2>::new(4, [a40 as u8, a41 as u8]);
-    let room6 = Room::<2>::new(6, [a60 as u8, a61 as u8]);
-    let room8 = Room::<2>::new(8, [a80 as u8, a81 as u8]);
-
-    [room2, room4, room6, room8]
+fn input_generator1(raw_input: &str) -> Result<Input1, ParseError> {
+    let [a2, a4, a6, a8] = parse_rooms(raw_input)?;
+
+    let room2 = Room::<2>::new(2, a2);
+    let room4 = Room::<2>::new(4, a4);
+    let room6 = Room::<2>::new(6, a6);
+    let room8 = Room::<2>::new(8, a8);
+
+    Ok([room2, room4, room6, room8])
 }
 
 #[aoc_generator(day23 part2, nordzilla)]
-fn input_generator2(raw_input: &str) -> Input2 {
-    let mut lines = raw_input.lines();
-    lines.next();
-    lines.next();
-
-    let [a20, a40, a60, a80]: [char; 4];
-    let [a23, a43, a63, a83]: [char; 4];
-    scan!(lines.next().unwrap().bytes() => "###{}#{}#{}#{}###", a20, a40, a60, a80);
-    scan!(lines.next().unwrap().bytes() => "  #{}#{}#{}#{}#", a23, a43, a63, a83);
-
-    let room2 = Room::<4>::new(2, [a20 as u8, b'D', b'D', a23 as u8]);
-    let room4 = Room::<4>::new(4, [a40 as u8, b'C', b'B', a43 as u8]);
-    let room6 = Room::<4>::new(6, [a60 as u8, b'B', b'A', a63 as u8]);
-    let room8 = Room::<4>::new(8, [a80 as u8, b'A', b'C', a83 as u8]);
-
-    [room2, room4, room6, room8]
+fn input_generator2(raw_input: &str) -> Result<Input2, ParseError> {
+    let [[a20, a23], [a40, a43], [a60, a63], [a80, a83]] = parse_rooms(raw_input)?;
+
+    let room2 = Room::<4>::new(2, [a20, b'D', b'D', a23]);
+    let room4 = Room::<4>::new(4, [a40, b'C', b'B', a43]);
+    let room6 = Room::<4>::new(6, [a60, b'B', b'A', a63]);
+    let room8 = Room::<4>::new(8, [a80, b'A', b'C', a83]);
+
+    Ok([room2, room4, room6, room8])
 }
 
 impl<const N: usize> Room<N> {
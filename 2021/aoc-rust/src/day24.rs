@@ -1,14 +1,23 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
+use aoc_common::{
+    num::{concat_digits, digits},
+    parse::{self, ParseError},
+};
 use aoc_runner_derive::*;
 use flow_control::return_if;
 use rayon::prelude::*;
 
+mod compiler;
+mod symbolic;
+
+use compiler::Compiled;
+
 type Input = Vec<Instruction>;
 type Output = i64;
 
 #[derive(Debug, Copy, Clone)]
-enum Var {
+pub enum Var {
     W,
     X,
     Y,
@@ -16,21 +25,23 @@ enum Var {
     Lit(i64),
 }
 
-impl FromStr for Var {
-    type Err = <i64 as FromStr>::Err;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match s {
-            "w" => Var::W,
-            "x" => Var::X,
-            "y" => Var::Y,
-            "z" => Var::Z,
-            n => Var::Lit(n.parse::<i64>()?),
-        })
+impl Var {
+    /// Parses `text`, which must be a slice of `input`, as a register, or as either a
+    /// register or a number if `literal` is set.
+    fn parse(input: &str, text: &str, literal: bool) -> Result<Self, ParseError> {
+        match text {
+            "w" => Ok(Var::W),
+            "x" => Ok(Var::X),
+            "y" => Ok(Var::Y),
+            "z" => Ok(Var::Z),
+            n if literal => parse::value(input, n, "a register or a number").map(Var::Lit),
+            _ => Err(ParseError::new(input, text, "a register")),
+        }
     }
 }
 
 #[derive(Debug, Copy, Clone)]
-enum Instruction {
+pub enum Instruction {
     Inp(Var),
     Add(Var, Var),
     Mul(Var, Var),
@@ -39,52 +50,110 @@ enum Instruction {
     Eql(Var, Var),
 }
 
+impl Instruction {
+    /// Parses `line`, which must be a slice of `input`, as an instruction such as
+    /// `add x 5`.
+    pub fn parse(input: &str, line: &str) -> Result<Self, ParseError> {
+        let end = &line[line.len()..];
+        let mut words = line.split_whitespace();
+        let opcode = words.next().unwrap_or(end);
+        let mut operand = |literal| Var::parse(input, words.next().unwrap_or(end), literal);
+        let instruction = match opcode {
+            "inp" => Instruction::Inp(operand(false)?),
+            "add" => Instruction::Add(operand(false)?, operand(true)?),
+            "mul" => Instruction::Mul(operand(false)?, operand(true)?),
+            "div" => Instruction::Div(operand(false)?, operand(true)?),
+            "mod" => Instruction::Mod(operand(false)?, operand(true)?),
+            "eql" => Instruction::Eql(operand(false)?, operand(true)?),
+            _ => {
+                let expected = "inp, add, mul, div, mod, or eql";
+                return Err(ParseError::new(input, opcode, expected));
+            }
+        };
+        match words.next() {
+            Some(extra) => Err(ParseError::new(input, extra, "the end of the line")),
+            None => Ok(instruction),
+        }
+    }
+}
+
 impl FromStr for Instruction {
-    type Err = <Var as FromStr>::Err;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(match &s[0..3] {
-            "inp" => Instruction::Inp((&s[4..5]).parse::<Var>()?),
-            "add" => Instruction::Add((&s[4..5]).parse::<Var>()?, (&s[6..]).parse::<Var>()?),
-            "mul" => Instruction::Mul((&s[4..5]).parse::<Var>()?, (&s[6..]).parse::<Var>()?),
-            "div" => Instruction::Div((&s[4..5]).parse::<Var>()?, (&s[6..]).parse::<Var>()?),
-            "mod" => Instruction::Mod((&s[4..5]).parse::<Var>()?, (&s[6..]).parse::<Var>()?),
-            "eql" => Instruction::Eql((&s[4..5]).parse::<Var>()?, (&s[6..]).parse::<Var>()?),
-            _ => panic!("at the disco!"),
-        })
+        Instruction::parse(s, s)
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Var::W => write!(f, "w"),
+            Var::X => write!(f, "x"),
+            Var::Y => write!(f, "y"),
+            Var::Z => write!(f, "z"),
+            Var::Lit(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Inp(var) => write!(f, "inp {}", var),
+            Instruction::Add(lhs, rhs) => write!(f, "add {} {}", lhs, rhs),
+            Instruction::Mul(lhs, rhs) => write!(f, "mul {} {}", lhs, rhs),
+            Instruction::Div(lhs, rhs) => write!(f, "div {} {}", lhs, rhs),
+            Instruction::Mod(lhs, rhs) => write!(f, "mod {} {}", lhs, rhs),
+            Instruction::Eql(lhs, rhs) => write!(f, "eql {} {}", lhs, rhs),
+        }
     }
 }
 
 #[aoc_generator(day24, part1, nordzilla)]
 #[aoc_generator(day24, part2, nordzilla)]
-fn input_generator(raw_input: &str) -> Input {
+#[aoc_generator(day24, part1, compiled)]
+#[aoc_generator(day24, part2, compiled)]
+#[aoc_generator(day24, part1, symbolic)]
+#[aoc_generator(day24, part2, symbolic)]
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
     raw_input
         .lines()
-        .flat_map(Instruction::from_str)
-        .filter(|instruction| !matches!(instruction, Instruction::Div(_, Var::Lit(1))))
+        .map(|line| Instruction::parse(raw_input, line))
+        .filter(|instruction| !matches!(instruction, Ok(Instruction::Div(_, Var::Lit(1)))))
         .collect()
 }
 
 #[derive(Debug, Default, Clone, Copy)]
-struct Monad<'a> {
+pub struct Monad<'a> {
     w: i64,
     x: i64,
     y: i64,
     z: i64,
     counter: usize,
     instructions: &'a [Instruction],
+    compiled: Option<&'a Compiled>,
 }
 
 //====================================================================================
 // ALU operations
 //====================================================================================
 impl<'a> Monad<'a> {
-    fn new(instructions: &'a [Instruction]) -> Self {
+    pub fn new(instructions: &'a [Instruction]) -> Self {
         Self {
             instructions,
             ..Self::default()
         }
     }
 
+    /// Runs the compiled instructions instead of interpreting them. They must have been
+    /// compiled with a checkpoint at every critical instruction that observes x and z.
+    fn with_compiled(self, compiled: &'a Compiled) -> Self {
+        Self {
+            compiled: Some(compiled),
+            ..self
+        }
+    }
+
     fn var(&mut self, var: Var) -> Option<&mut i64> {
         match var {
             Var::W => Some(&mut self.w),
@@ -116,10 +185,18 @@ impl<'a> Monad<'a> {
     ) -> Option<()> {
         match instruction {
             Instruction::Inp(v) => Monad::inp(self.var(v)?, digits)?,
-            Instruction::Add(lhs, rhs) => *self.var(lhs)? += self.value(rhs),
-            Instruction::Mul(lhs, rhs) => *self.var(lhs)? *= self.value(rhs),
-            Instruction::Div(lhs, rhs) => *self.var(lhs)? /= self.value(rhs),
-            Instruction::Mod(lhs, rhs) => *self.var(lhs)? %= self.value(rhs),
+            Instruction::Add(lhs, rhs) => {
+                *self.var(lhs)? = self.value(lhs).checked_add(self.value(rhs))?
+            }
+            Instruction::Mul(lhs, rhs) => {
+                *self.var(lhs)? = self.value(lhs).checked_mul(self.value(rhs))?
+            }
+            Instruction::Div(lhs, rhs) => {
+                *self.var(lhs)? = self.value(lhs).checked_div(self.value(rhs))?
+            }
+            Instruction::Mod(lhs, rhs) => {
+                *self.var(lhs)? = self.value(lhs).checked_rem(self.value(rhs))?
+            }
             Instruction::Eql(lhs, rhs) => {
                 *self.var(lhs)? = (self.value(lhs) == self.value(rhs)) as i64
             }
@@ -129,22 +206,43 @@ impl<'a> Monad<'a> {
 }
 
 //====================================================================================
-// Numeric manipulation helper functions
+// Stepping
 //====================================================================================
+impl Monad<'_> {
+    /// The index of the next instruction to evaluate.
+    pub fn counter(&self) -> usize {
+        self.counter
+    }
 
-/// Returns the count of digits in a number, or 0 if the number itself is 0.
-fn digit_count(n: i64) -> u32 {
-    return_if!(n == 0, 0);
-    1 + (n as f64).log10() as u32
-}
+    /// The values of the w, x, y, and z registers, in that order.
+    pub fn registers(&self) -> [i64; 4] {
+        [self.w, self.x, self.y, self.z]
+    }
 
-/// Returns an iterator over the digits of a number from highest place value to lowest place value.
-fn digits_iter(n: i64) -> impl Iterator<Item = i64> {
-    (0..digit_count(n))
-        .rev()
-        .map(move |exp| n / 10_i64.pow(exp) % 10)
+    /// The next instruction to evaluate, or `None` at the end of the program.
+    pub fn next_instruction(&self) -> Option<Instruction> {
+        self.instructions.get(self.counter).copied()
+    }
+
+    /// Evaluates the next instruction and moves the counter past it.
+    ///
+    /// Returns `None` without changing anything at the end of the program, if the
+    /// instruction needs a digit and none are left, if it writes to a literal, or if it
+    /// divides or takes a modulo by zero or overflows.
+    pub fn step(&mut self, digits: &mut impl Iterator<Item = i64>) -> Option<Instruction> {
+        let instruction = self.next_instruction()?;
+        let mut stepped = *self;
+        stepped.eval(instruction, digits)?;
+        stepped.counter += 1;
+        *self = stepped;
+        Some(instruction)
+    }
 }
 
+//====================================================================================
+// Numeric manipulation helper functions
+//====================================================================================
+
 /// Retuns a parallel iterator of all numbers with the specified number of `digits` that do not contain 0
 /// in any digit. The numbers will start with all 1's and end with all 9's, for the given digit length.
 fn increasing_segment_candidates(digits: u32) -> impl ParallelIterator<Item = i64> {
@@ -189,10 +287,13 @@ fn decreasing_segment_candidates(digits: u32) -> impl ParallelIterator<Item = i6
     .into_par_iter()
 }
 
-/// Returns the concatenation of two numbers.
-/// e.g. concat_segments(123, 456) == 123456
-fn concat_segments(lhs: i64, rhs: i64) -> i64 {
-    lhs * 10_i64.pow(digit_count(rhs)) + rhs
+/// Appends the digits of `segment` to `number`, where a segment of 0 is the empty segment
+/// generated for zero digits.
+fn append_segment(number: i64, segment: i64) -> i64 {
+    match segment {
+        0 => number,
+        _ => concat_digits(number, segment),
+    }
 }
 
 //====================================================================================
@@ -217,6 +318,16 @@ impl<'a> Monad<'a> {
             .collect()
     }
 
+    /// Compiles the instructions with a checkpoint at every critical instruction.
+    fn compile(self) -> Compiled {
+        Compiled::new(
+            self.instructions,
+            &self.find_critical_instructions(),
+            &[Var::X, Var::Z],
+        )
+        .unwrap()
+    }
+
     /// A solution segment is valid only if the x-register is zero after evaluating
     /// the critical instruction with regard to this segment.
     ///
@@ -228,14 +339,24 @@ impl<'a> Monad<'a> {
         segment_candidate: i64,
         critical_instruction: usize,
     ) -> Option<(Self, i64)> {
-        let mut digits = digits_iter(segment_candidate);
-        for &instruction in self
-            .instructions
-            .iter()
-            .skip(self.counter)
-            .take(critical_instruction - self.counter)
-        {
-            self.eval(instruction, &mut digits)?;
+        let mut digits = digits(segment_candidate);
+        if let Some(compiled) = self.compiled {
+            let mut registers = [self.w, self.x, self.y, self.z];
+            compiled.run(
+                &mut registers,
+                self.counter..critical_instruction,
+                &mut digits,
+            )?;
+            [self.w, self.x, self.y, self.z] = registers;
+        } else {
+            for &instruction in self
+                .instructions
+                .iter()
+                .skip(self.counter)
+                .take(critical_instruction - self.counter)
+            {
+                self.eval(instruction, &mut digits)?;
+            }
         }
         self.counter += critical_instruction - self.counter;
         (self.x == 0).then(|| (self, segment_candidate))
@@ -273,7 +394,7 @@ impl<'a> Monad<'a> {
                         generate_candidates,
                         &critical_instructions[1..],
                     )
-                    .map(|next_segment| concat_segments(previous_segment, next_segment))
+                    .map(|next_segment| append_segment(previous_segment, next_segment))
             })
     }
 
@@ -298,3 +419,104 @@ fn solve_part2(instructions: &Input) -> Output {
         .first_valid_number(increasing_segment_candidates)
         .unwrap()
 }
+
+#[aoc(day24, part1, compiled)]
+fn solve_part1_compiled(instructions: &Input) -> Output {
+    let monad = Monad::new(instructions);
+    let compiled = monad.compile();
+    monad
+        .with_compiled(&compiled)
+        .first_valid_number(decreasing_segment_candidates)
+        .unwrap()
+}
+
+#[aoc(day24, part2, compiled)]
+fn solve_part2_compiled(instructions: &Input) -> Output {
+    let monad = Monad::new(instructions);
+    let compiled = monad.compile();
+    monad
+        .with_compiled(&compiled)
+        .first_valid_number(increasing_segment_candidates)
+        .unwrap()
+}
+
+#[aoc(day24, part1, symbolic)]
+fn solve_part1_symbolic(instructions: &Input) -> Output {
+    symbolic::model_number(instructions, true).unwrap()
+}
+
+#[aoc(day24, part2, symbolic)]
+fn solve_part2_symbolic(instructions: &Input) -> Output {
+    symbolic::model_number(instructions, false).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Builds a MONAD program from the `div z`, `add x`, and `add y` operands of each
+    /// of its blocks, which are all that differ between puzzle inputs.
+    pub(super) fn monad(blocks: &[(i64, i64, i64)]) -> Input {
+        let program: String = blocks
+            .iter()
+            .map(|(divisor, check, offset)| {
+                format!(
+                    "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\nadd x {}\neql x w\neql x 0\n\
+                     mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\n\
+                     add y {}\nmul y x\nadd z y\n",
+                    divisor, check, offset,
+                )
+            })
+            .collect();
+        input_generator(&program).unwrap()
+    }
+
+    pub(super) const EXAMPLE_BLOCKS: [(i64, i64, i64); 14] = [
+        (1, 15, 1),
+        (1, 11, 13),
+        (26, -7, 14),
+        (26, -6, 1),
+        (1, 13, 15),
+        (1, 15, 9),
+        (1, 10, 8),
+        (1, 14, 4),
+        (26, -5, 11),
+        (26, -5, 1),
+        (1, 11, 1),
+        (26, -2, 1),
+        (26, -10, 1),
+        (26, -9, 13),
+    ];
+
+    #[test]
+    fn test_nordzilla() {
+        let instructions = monad(&EXAMPLE_BLOCKS);
+        assert_eq!(solve_part1(&instructions), 93943969899889);
+        assert_eq!(solve_part2(&instructions), 61711212142117);
+    }
+
+    #[test]
+    fn test_compiled() {
+        let instructions = monad(&EXAMPLE_BLOCKS);
+        assert_eq!(solve_part1_compiled(&instructions), 93943969899889);
+        assert_eq!(solve_part2_compiled(&instructions), 61711212142117);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = input_generator("inp w\nadd x 5\nsub y 2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 1: expected inp, add, mul, div, mod, or eql, found \"sub y 2\"",
+        );
+        let error = Instruction::parse("mul 3 x", "mul 3 x").unwrap_err();
+        assert_eq!((error.column, error.expected.as_str()), (5, "a register"));
+        let error = Instruction::parse("add x", "add x").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 6: expected a register or a number, found the end of the line",
+        );
+        let error = Instruction::parse("eql x w z", "eql x w z").unwrap_err();
+        assert_eq!(error.expected, "the end of the line");
+    }
+}
@@ -0,0 +1,653 @@
+//! Compiles ALU programs into closures so that they can be run many times over.
+//!
+//! The program is first lowered into three-address operations while tracking the range
+//! of values that each register can hold. Operations whose result is already known are
+//! folded away, such as the `mul x 0` that clears a register before `add x z` copies into
+//! it, or an `eql` between an input digit and a value that can never be a digit. A
+//! backwards liveness pass then drops every operation whose result is never observed,
+//! unless it might divide or take a modulo by zero or overflow, which stops the program
+//! just as it stops [`Monad::eval`](super::Monad::eval).
+//! What remains is split into blocks at each `inp` instruction and at each requested
+//! checkpoint, and every operation becomes a closure that is specialized for its register
+//! and constant operands.
+//!
+//! Folding assumes that the program starts with every register at zero, that its blocks
+//! are run in order, and that every input is a digit from 1 to 9.
+
+use super::{Instruction, Var};
+use std::{fmt, ops::Range};
+
+/// The values of the w, x, y, and z registers, in that order.
+pub type Registers = [i64; 4];
+
+/// Returns `None` if the operation divides or takes a modulo by zero or overflows.
+type Operation = Box<dyn Fn(&mut Registers) -> Option<()> + Send + Sync>;
+
+type Interval = (i64, i64);
+
+const UNKNOWN: Interval = (i64::MIN, i64::MAX);
+const DIGITS: Interval = (1, 9);
+
+fn register(var: Var) -> Option<usize> {
+    match var {
+        Var::W => Some(0),
+        Var::X => Some(1),
+        Var::Y => Some(2),
+        Var::Z => Some(3),
+        Var::Lit(_) => None,
+    }
+}
+
+// =====================================================================================
+// Operations
+// =====================================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    Reg(usize),
+    Const(i64),
+}
+
+use Operand::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Set,
+    Add,
+    Mul,
+    Div,
+    Mod,
+    Eql,
+}
+
+/// Stores `lhs <kind> rhs` in `dst`. A [`Kind::Set`] stores `rhs` and ignores `lhs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Op {
+    kind: Kind,
+    dst: usize,
+    lhs: Operand,
+    rhs: Operand,
+    /// Whether the operation might fail, so that it must run even if its result is never
+    /// observed.
+    fallible: bool,
+}
+
+impl Op {
+    fn set(dst: usize, value: Operand) -> Self {
+        Self {
+            kind: Kind::Set,
+            dst,
+            lhs: Const(0),
+            rhs: value,
+            fallible: false,
+        }
+    }
+
+    /// The registers whose values the operation reads.
+    fn reads(self) -> impl Iterator<Item = usize> {
+        let lhs = (self.kind != Kind::Set).then_some(self.lhs);
+        lhs.into_iter()
+            .chain(Some(self.rhs))
+            .filter_map(|operand| match operand {
+                Reg(r) => Some(r),
+                Const(_) => None,
+            })
+    }
+}
+
+/// The range of values that the result of an operation can take, given the ranges of
+/// its operands.
+fn interval(kind: Kind, (a_lo, a_hi): Interval, (b_lo, b_hi): Interval) -> Interval {
+    match kind {
+        Kind::Set => (b_lo, b_hi),
+        Kind::Add => a_lo
+            .checked_add(b_lo)
+            .zip(a_hi.checked_add(b_hi))
+            .unwrap_or(UNKNOWN),
+        Kind::Mul => {
+            let products = [(a_lo, b_lo), (a_lo, b_hi), (a_hi, b_lo), (a_hi, b_hi)]
+                .iter()
+                .map(|&(a, b)| a.checked_mul(b))
+                .collect::<Option<Vec<_>>>();
+            match products {
+                Some(products) => (
+                    *products.iter().min().unwrap(),
+                    *products.iter().max().unwrap(),
+                ),
+                None => UNKNOWN,
+            }
+        }
+        Kind::Div if b_lo == b_hi && b_lo > 0 => (a_lo / b_lo, a_hi / b_lo),
+        Kind::Mod if b_lo == b_hi && b_lo > 0 && a_lo >= 0 && a_hi < b_lo => (a_lo, a_hi),
+        Kind::Mod if b_lo == b_hi && b_lo > 0 && a_lo >= 0 => (0, b_lo - 1),
+        Kind::Eql if a_hi < b_lo || b_hi < a_lo => (0, 0),
+        Kind::Eql if a_lo == a_hi && b_lo == b_hi => (1, 1),
+        Kind::Eql => (0, 1),
+        Kind::Div | Kind::Mod => UNKNOWN,
+    }
+}
+
+/// Whether the operation might divide or take a modulo by zero or overflow, given the
+/// ranges of its operands.
+fn is_fallible(kind: Kind, (a_lo, a_hi): Interval, (b_lo, b_hi): Interval) -> bool {
+    match kind {
+        Kind::Set | Kind::Eql => false,
+        Kind::Add => a_lo.checked_add(b_lo).zip(a_hi.checked_add(b_hi)).is_none(),
+        Kind::Mul => [(a_lo, b_lo), (a_lo, b_hi), (a_hi, b_lo), (a_hi, b_hi)]
+            .iter()
+            .any(|&(a, b)| a.checked_mul(b).is_none()),
+        Kind::Div | Kind::Mod => {
+            (b_lo <= 0 && 0 <= b_hi) || (a_lo == i64::MIN && b_lo <= -1 && -1 <= b_hi)
+        }
+    }
+}
+
+/// Whether the operation leaves a register with the given range unchanged.
+fn is_identity(kind: Kind, (lo, hi): Interval, rhs: Operand) -> bool {
+    match (kind, rhs) {
+        (Kind::Add, Const(0)) | (Kind::Mul, Const(1)) | (Kind::Div, Const(1)) => true,
+        (Kind::Mod, Const(n)) => 0 <= lo && hi < n,
+        _ => false,
+    }
+}
+
+// =====================================================================================
+// Lowering
+// =====================================================================================
+
+/// A straight run of operations that starts at an `inp` instruction or a checkpoint.
+struct Block {
+    /// The index of the instruction that the block starts at.
+    start: usize,
+    /// The register that the block reads an input digit into before its operations.
+    input: Option<usize>,
+    ops: Vec<Op>,
+}
+
+/// What is known about the registers at some point in the program.
+struct Known {
+    ranges: [Interval; 4],
+    /// Registers whose value is known but has not been stored yet.
+    pending: [bool; 4],
+}
+
+impl Known {
+    fn operand(&self, var: Var) -> (Operand, Interval) {
+        match register(var) {
+            Some(r) if self.ranges[r].0 == self.ranges[r].1 => {
+                (Const(self.ranges[r].0), self.ranges[r])
+            }
+            Some(r) => (Reg(r), self.ranges[r]),
+            None => match var {
+                Var::Lit(n) => (Const(n), (n, n)),
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    /// Stores every known value that has not been stored yet.
+    fn flush(&mut self, ops: &mut Vec<Op>) {
+        for r in 0..4 {
+            if self.pending[r] {
+                ops.push(Op::set(r, Const(self.ranges[r].0)));
+                self.pending[r] = false;
+            }
+        }
+    }
+}
+
+/// Lowers the instructions into blocks of operations, folding every operation whose
+/// result is known. Returns `None` if an instruction writes to a literal.
+fn lower(instructions: &[Instruction], checkpoints: &[usize]) -> Option<Vec<Block>> {
+    let mut known = Known {
+        ranges: [(0, 0); 4],
+        pending: [false; 4],
+    };
+    let mut blocks: Vec<Block> = Vec::new();
+    for (index, &instruction) in instructions.iter().enumerate() {
+        let is_input = matches!(instruction, Instruction::Inp(_));
+        if blocks.is_empty() || is_input || checkpoints.contains(&index) {
+            if let Some(block) = blocks.last_mut() {
+                known.flush(&mut block.ops);
+            }
+            blocks.push(Block {
+                start: index,
+                input: None,
+                ops: Vec::new(),
+            });
+        }
+        let block = blocks.last_mut().unwrap();
+
+        let (kind, lhs, rhs) = match instruction {
+            Instruction::Inp(var) => {
+                let dst = register(var)?;
+                block.input = Some(dst);
+                known.ranges[dst] = DIGITS;
+                continue;
+            }
+            Instruction::Add(lhs, rhs) => (Kind::Add, lhs, rhs),
+            Instruction::Mul(lhs, rhs) => (Kind::Mul, lhs, rhs),
+            Instruction::Div(lhs, rhs) => (Kind::Div, lhs, rhs),
+            Instruction::Mod(lhs, rhs) => (Kind::Mod, lhs, rhs),
+            Instruction::Eql(lhs, rhs) => (Kind::Eql, lhs, rhs),
+        };
+        let dst = register(lhs)?;
+        let ((lhs, lhs_range), (rhs, rhs_range)) = (known.operand(lhs), known.operand(rhs));
+        let range = interval(kind, lhs_range, rhs_range);
+
+        if range.0 == range.1 {
+            known.pending[dst] = true;
+        } else if !is_identity(kind, lhs_range, rhs) {
+            block.ops.push(match (kind, lhs) {
+                (Kind::Add, Const(0)) | (Kind::Mul, Const(1)) => Op::set(dst, rhs),
+                _ => Op {
+                    kind,
+                    dst,
+                    lhs,
+                    rhs,
+                    fallible: is_fallible(kind, lhs_range, rhs_range),
+                },
+            });
+            known.pending[dst] = false;
+        }
+        known.ranges[dst] = range;
+    }
+    if let Some(block) = blocks.last_mut() {
+        known.flush(&mut block.ops);
+    }
+    Some(blocks)
+}
+
+/// Removes every infallible operation whose result is overwritten or never observed. The
+/// observed registers are read at the end of the program and at the start of each
+/// checkpoint.
+fn eliminate_dead_registers(blocks: &mut [Block], checkpoints: &[usize], observed: &[usize]) {
+    let mut live = [false; 4];
+    observed.iter().for_each(|&r| live[r] = true);
+    for block in blocks.iter_mut().rev() {
+        let mut ops = Vec::with_capacity(block.ops.len());
+        for op in block.ops.drain(..).rev() {
+            if !live[op.dst] && !op.fallible {
+                continue;
+            }
+            live[op.dst] = false;
+            op.reads().for_each(|r| live[r] = true);
+            ops.push(op);
+        }
+        ops.reverse();
+        block.ops = ops;
+
+        if let Some(dst) = block.input {
+            live[dst] = false;
+        }
+        if checkpoints.contains(&block.start) {
+            observed.iter().for_each(|&r| live[r] = true);
+        }
+    }
+}
+
+// =====================================================================================
+// Closures
+// =====================================================================================
+
+/// Builds a closure that computes `$result` from the operands `$a` and `$b` of the
+/// operation, reading only the operands that are registers. The closure fails if
+/// `$result` is `None`.
+macro_rules! specialize {
+    ($op:expr, |$a:ident, $b:ident| $result:expr) => {{
+        let dst = $op.dst;
+        let operation: Operation = match ($op.lhs, $op.rhs) {
+            (Reg(lhs), Reg(rhs)) => Box::new(move |registers: &mut Registers| {
+                let ($a, $b) = (registers[lhs], registers[rhs]);
+                registers[dst] = $result?;
+                Some(())
+            }),
+            (Reg(lhs), Const($b)) => Box::new(move |registers: &mut Registers| {
+                let $a = registers[lhs];
+                registers[dst] = $result?;
+                Some(())
+            }),
+            (Const($a), Reg(rhs)) => Box::new(move |registers: &mut Registers| {
+                let $b = registers[rhs];
+                registers[dst] = $result?;
+                Some(())
+            }),
+            (Const($a), Const($b)) => Box::new(move |registers: &mut Registers| {
+                registers[dst] = $result?;
+                Some(())
+            }),
+        };
+        operation
+    }};
+}
+
+fn closure(op: Op) -> Operation {
+    match op.kind {
+        Kind::Set => specialize!(op, |_a, b| Some(b)),
+        Kind::Add => specialize!(op, |a, b| a.checked_add(b)),
+        Kind::Mul => specialize!(op, |a, b| a.checked_mul(b)),
+        Kind::Div => specialize!(op, |a, b| a.checked_div(b)),
+        Kind::Mod => specialize!(op, |a, b| a.checked_rem(b)),
+        Kind::Eql => specialize!(op, |a, b| Some((a == b) as i64)),
+    }
+}
+
+// =====================================================================================
+// Compiled programs
+// =====================================================================================
+
+/// An ALU program compiled into one closure per operation that survived optimization.
+pub struct Compiled {
+    blocks: Vec<Block>,
+    closures: Vec<Vec<Operation>>,
+}
+
+impl Compiled {
+    /// Compiles the instructions so that they can be run up to any of the checkpoints,
+    /// which are instruction indices. Only the observed registers are guaranteed to hold
+    /// the right values at a checkpoint or at the end of the program.
+    ///
+    /// Returns `None` if an instruction writes to a literal.
+    pub fn new(
+        instructions: &[Instruction],
+        checkpoints: &[usize],
+        observed: &[Var],
+    ) -> Option<Self> {
+        let observed = observed
+            .iter()
+            .map(|&var| register(var))
+            .collect::<Option<Vec<_>>>()?;
+        let mut blocks = lower(instructions, checkpoints)?;
+        eliminate_dead_registers(&mut blocks, checkpoints, &observed);
+        let closures = blocks
+            .iter()
+            .map(|block| block.ops.iter().copied().map(closure).collect())
+            .collect();
+        Some(Self { blocks, closures })
+    }
+
+    /// Runs the instructions within the range, which must start at the beginning of the
+    /// program or at a checkpoint, and end at a checkpoint or the end of the program.
+    ///
+    /// Returns `None` if the program runs out of digits, divides or takes a modulo by zero,
+    /// or overflows.
+    pub fn run(
+        &self,
+        registers: &mut Registers,
+        range: Range<usize>,
+        digits: &mut impl Iterator<Item = i64>,
+    ) -> Option<()> {
+        let first = self
+            .blocks
+            .partition_point(|block| block.start < range.start);
+        debug_assert!(
+            range.start == 0
+                || self
+                    .blocks
+                    .get(first)
+                    .is_none_or(|block| block.start == range.start)
+        );
+        for (block, closures) in self.blocks[first..].iter().zip(&self.closures[first..]) {
+            if block.start >= range.end {
+                break;
+            }
+            if let Some(dst) = block.input {
+                registers[dst] = digits.next()?;
+            }
+            for closure in closures {
+                closure(registers)?;
+            }
+        }
+        Some(())
+    }
+}
+
+impl fmt::Debug for Compiled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.blocks
+                    .iter()
+                    .map(|block| (block.start, block.input, &block.ops)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::test::{monad, EXAMPLE_BLOCKS};
+    use super::super::{input_generator, Monad};
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn op_count(compiled: &Compiled) -> usize {
+        compiled.blocks.iter().map(|block| block.ops.len()).sum()
+    }
+
+    fn random_digits(rng: &mut StdRng, count: usize) -> Vec<i64> {
+        (0..count).map(|_| rng.gen_range(1..=9)).collect()
+    }
+
+    fn try_interpret(
+        monad: &mut Monad,
+        range: Range<usize>,
+        digits: &mut impl Iterator<Item = i64>,
+    ) -> Option<()> {
+        let instructions = monad.instructions;
+        for &instruction in &instructions[range] {
+            monad.eval(instruction, digits)?;
+        }
+        Some(())
+    }
+
+    fn interpret(monad: &mut Monad, range: Range<usize>, digits: &mut impl Iterator<Item = i64>) {
+        try_interpret(monad, range, digits).unwrap();
+    }
+
+    #[test]
+    fn test_matches_interpreter_on_monad() {
+        let instructions = monad(&EXAMPLE_BLOCKS);
+        let all = Compiled::new(&instructions, &[], &[Var::W, Var::X, Var::Y, Var::Z]).unwrap();
+        let z = Compiled::new(&instructions, &[], &[Var::Z]).unwrap();
+        assert!(op_count(&all) < instructions.len() / 2);
+
+        let mut rng = StdRng::seed_from_u64(24);
+        for _ in 0..1000 {
+            let digits = random_digits(&mut rng, 14);
+            let mut expected = Monad::new(&instructions);
+            interpret(
+                &mut expected,
+                0..instructions.len(),
+                &mut digits.iter().copied(),
+            );
+
+            let mut registers = Registers::default();
+            all.run(
+                &mut registers,
+                0..instructions.len(),
+                &mut digits.iter().copied(),
+            )
+            .unwrap();
+            assert_eq!(registers, [expected.w, expected.x, expected.y, expected.z]);
+
+            let mut registers = Registers::default();
+            z.run(
+                &mut registers,
+                0..instructions.len(),
+                &mut digits.iter().copied(),
+            )
+            .unwrap();
+            assert_eq!(registers[3], expected.z);
+        }
+    }
+
+    fn random_var(rng: &mut StdRng) -> Var {
+        [Var::W, Var::X, Var::Y, Var::Z][rng.gen_range(0..4)]
+    }
+
+    fn random_operand(rng: &mut StdRng, range: Range<i64>) -> Var {
+        match rng.gen_bool(0.5) {
+            true => random_var(rng),
+            false => Var::Lit(rng.gen_range(range)),
+        }
+    }
+
+    /// A random program that neither divides by zero nor overflows.
+    fn random_program(rng: &mut StdRng, len: usize) -> Vec<Instruction> {
+        (0..len)
+            .map(|_| {
+                let dst = random_var(rng);
+                match rng.gen_range(0..6) {
+                    0 => Instruction::Inp(dst),
+                    1 => Instruction::Add(dst, random_operand(rng, -30..30)),
+                    2 => Instruction::Mul(dst, Var::Lit(rng.gen_range(-5..6))),
+                    3 => Instruction::Div(dst, Var::Lit(rng.gen_range(1..30))),
+                    4 => Instruction::Mod(dst, Var::Lit(rng.gen_range(1..30))),
+                    _ => Instruction::Eql(dst, random_operand(rng, -2..11)),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_interpreter_on_random_programs() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let checkpoints = [5, 10, 15];
+        let segments = [0..5, 5..10, 10..15, 15..20];
+        for _ in 0..500 {
+            let instructions = random_program(&mut rng, 20);
+            let compiled = Compiled::new(&instructions, &checkpoints, &[Var::X, Var::Z]).unwrap();
+            for _ in 0..10 {
+                let digits = random_digits(&mut rng, 20);
+                let mut expected = Monad::new(&instructions);
+                let mut expected_digits = digits.iter().copied();
+                let mut registers = Registers::default();
+                let mut digits = digits.iter().copied();
+                for segment in segments.iter().cloned() {
+                    interpret(&mut expected, segment.clone(), &mut expected_digits);
+                    compiled.run(&mut registers, segment, &mut digits).unwrap();
+                    assert_eq!(
+                        (registers[1], registers[3]),
+                        (expected.x, expected.z),
+                        "{:?}",
+                        instructions,
+                    );
+                }
+            }
+        }
+    }
+
+    /// A random program that may divide by zero or overflow.
+    fn random_fallible_program(rng: &mut StdRng, len: usize) -> Vec<Instruction> {
+        (0..len)
+            .map(|_| {
+                let dst = random_var(rng);
+                match rng.gen_range(0..7) {
+                    0 => Instruction::Inp(dst),
+                    1 => Instruction::Mul(dst, Var::Lit(0)),
+                    2 => Instruction::Add(dst, random_operand(rng, -5..5)),
+                    3 => Instruction::Mul(dst, Var::Lit(i64::MAX / rng.gen_range(1..4))),
+                    4 => Instruction::Div(dst, random_operand(rng, -1..2)),
+                    5 => Instruction::Mod(dst, random_operand(rng, 0..3)),
+                    _ => Instruction::Eql(dst, random_operand(rng, 0..3)),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fails_like_interpreter() {
+        let mut rng = StdRng::seed_from_u64(24);
+        let mut failures = 0;
+        for _ in 0..2000 {
+            let instructions = random_fallible_program(&mut rng, 12);
+            let compiled = Compiled::new(&instructions, &[], &[Var::Z]).unwrap();
+            let digits = random_digits(&mut rng, 12);
+            let mut expected = Monad::new(&instructions);
+            let expected = try_interpret(
+                &mut expected,
+                0..instructions.len(),
+                &mut digits.iter().copied(),
+            )
+            .map(|()| expected.z);
+            let mut registers = Registers::default();
+            let actual = compiled
+                .run(
+                    &mut registers,
+                    0..instructions.len(),
+                    &mut digits.iter().copied(),
+                )
+                .map(|()| registers[3]);
+            assert_eq!(actual, expected, "{:?}", instructions);
+            failures += expected.is_none() as usize;
+        }
+        assert!(failures > 0);
+    }
+
+    #[test]
+    fn test_keeps_dead_failures() {
+        for program in [
+            "inp w\ndiv w 0\nmul w 0",
+            "inp w\nmod w x\nmul w 0",
+            "inp w\nmul w 9223372036854775807\nmul w 0",
+        ] {
+            let instructions = input_generator(program).unwrap();
+            let compiled = Compiled::new(&instructions, &[], &[Var::Z]).unwrap();
+            let mut registers = Registers::default();
+            assert_eq!(
+                compiled.run(&mut registers, 0..instructions.len(), &mut [9].into_iter()),
+                None,
+                "{}",
+                program,
+            );
+        }
+    }
+
+    #[test]
+    fn test_folds_and_eliminates() {
+        let instructions = input_generator(
+            "inp w\ninp z\nmul x 0\nadd x z\nmod x 26\nadd x 10\neql x w\n\
+             mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nadd z w",
+        )
+        .unwrap();
+        let compiled = Compiled::new(&instructions, &[], &[Var::Z]).unwrap();
+        assert_eq!(compiled.blocks.len(), 2);
+        assert!(compiled.blocks[0].ops.is_empty());
+        assert_eq!(
+            compiled.blocks[1].ops,
+            [Op {
+                kind: Kind::Add,
+                dst: 3,
+                lhs: Reg(3),
+                rhs: Reg(0),
+                fallible: false,
+            }],
+        );
+
+        let mut registers = Registers::default();
+        compiled
+            .run(
+                &mut registers,
+                0..instructions.len(),
+                &mut [4, 7].into_iter(),
+            )
+            .unwrap();
+        assert_eq!(registers[3], 11);
+    }
+
+    #[test]
+    fn test_runs_out_of_digits() {
+        let instructions = monad(&EXAMPLE_BLOCKS);
+        let compiled = Compiled::new(&instructions, &[], &[Var::Z]).unwrap();
+        let mut registers = Registers::default();
+        assert_eq!(
+            compiled.run(
+                &mut registers,
+                0..instructions.len(),
+                &mut [9; 13].into_iter()
+            ),
+            None,
+        );
+    }
+}
@@ -0,0 +1,582 @@
+//! Solves MONAD-style programs by running them over symbolic input digits.
+//!
+//! Every register holds an [`Expr`] over the input digits, which is simplified as it is
+//! built using the range of values that each expression can take. That is enough to see
+//! through the way that MONAD pushes digits onto `z` with `mul`/`add` and pops them back
+//! off with `mod`/`div`. Whenever an `eql` could go either way, execution forks into a
+//! path where its operands are equal and a path where they are not. The paths on which
+//! `z` ends at zero give the constraints that a model number must satisfy, and solving
+//! those directly yields the largest and smallest model numbers.
+
+use super::{Instruction, Var};
+use flow_control::return_if;
+use std::{collections::BTreeMap, error::Error, fmt};
+
+const DIGITS: (i64, i64) = (1, 9);
+
+// =====================================================================================
+// Expressions
+// =====================================================================================
+
+/// The value of a register as an expression over the input digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Const(i64),
+    /// The input digit read by the nth `inp` instruction.
+    Digit(usize),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+    Mod(Box<Expr>, Box<Expr>),
+    Eql(Box<Expr>, Box<Expr>),
+}
+
+use Expr::*;
+
+impl Expr {
+    /// The smallest and largest values that the expression can take.
+    pub fn range(&self) -> (i64, i64) {
+        match self {
+            Const(n) => (*n, *n),
+            Digit(_) => DIGITS,
+            Add(lhs, rhs) => {
+                let ((a, b), (c, d)) = (lhs.range(), rhs.range());
+                (a.saturating_add(c), b.saturating_add(d))
+            }
+            Mul(lhs, rhs) => {
+                let ((a, b), (c, d)) = (lhs.range(), rhs.range());
+                let corners = [
+                    a.saturating_mul(c),
+                    a.saturating_mul(d),
+                    b.saturating_mul(c),
+                    b.saturating_mul(d),
+                ];
+                (
+                    *corners.iter().min().unwrap(),
+                    *corners.iter().max().unwrap(),
+                )
+            }
+            Div(lhs, rhs) => match **rhs {
+                Const(n) if n > 0 => (lhs.range().0 / n, lhs.range().1 / n),
+                Const(n) if n < 0 => (lhs.range().1 / n, lhs.range().0 / n),
+                _ => (i64::MIN, i64::MAX),
+            },
+            Mod(lhs, rhs) => match **rhs {
+                Const(n) if n > 0 && lhs.range().0 >= 0 => (0, lhs.range().1.min(n - 1)),
+                Const(n) if n > 0 => (-(n - 1), n - 1),
+                _ => (i64::MIN, i64::MAX),
+            },
+            Eql(..) => (0, 1),
+        }
+    }
+
+    fn is_within(&self, min: i64, max: i64) -> bool {
+        let (low, high) = self.range();
+        min <= low && high <= max
+    }
+
+    /// Splits the expression into `(quotient, remainder)` if it has the form
+    /// `quotient * divisor + remainder`, where the quotient is non-negative and the
+    /// remainder is within `0..divisor`.
+    fn split_multiple(&self, divisor: i64) -> Option<(Expr, Expr)> {
+        let (multiple, remainder) = match self {
+            Add(lhs, rhs) => (&**lhs, (**rhs).clone()),
+            multiple => (multiple, Const(0)),
+        };
+        match multiple {
+            Mul(quotient, factor) if **factor == Const(divisor) => (quotient.range().0 >= 0
+                && remainder.is_within(0, divisor - 1))
+            .then(|| ((**quotient).clone(), remainder)),
+            _ => None,
+        }
+    }
+}
+
+// Constants are only folded together when the result fits in an i64. Otherwise the
+// expression is left as it is, just as the ALU would fail rather than wrap.
+
+fn add(lhs: Expr, rhs: Expr) -> Expr {
+    match (lhs, rhs) {
+        (Const(a), Const(b)) if a.checked_add(b).is_some() => Const(a + b),
+        (lhs @ Const(_), rhs @ Const(_)) => Add(Box::new(lhs), Box::new(rhs)),
+        (Const(0), e) | (e, Const(0)) => e,
+        (Const(n), e) => add(e, Const(n)),
+        (Add(e, c), Const(n)) => match *c {
+            Const(m) if m.checked_add(n).is_some() => add(*e, Const(m + n)),
+            c => Add(Box::new(Add(e, Box::new(c))), Box::new(Const(n))),
+        },
+        (lhs, rhs) => Add(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn mul(lhs: Expr, rhs: Expr) -> Expr {
+    match (lhs, rhs) {
+        (Const(a), Const(b)) if a.checked_mul(b).is_some() => Const(a * b),
+        (Const(0), _) | (_, Const(0)) => Const(0),
+        (Const(1), e) | (e, Const(1)) => e,
+        (Const(n), e) => Mul(Box::new(e), Box::new(Const(n))),
+        (lhs, rhs) => Mul(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn div(lhs: Expr, rhs: Expr) -> Expr {
+    match (lhs, rhs) {
+        (e, Const(1)) => e,
+        (Const(a), Const(b)) if b != 0 => Const(a / b),
+        (e, Const(n)) if n > 0 && e.is_within(0, n - 1) => Const(0),
+        (e, Const(n)) if n > 0 => match e.split_multiple(n) {
+            Some((quotient, _)) => quotient,
+            None => Div(Box::new(e), Box::new(Const(n))),
+        },
+        (lhs, rhs) => Div(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn modulo(lhs: Expr, rhs: Expr) -> Expr {
+    match (lhs, rhs) {
+        (Const(a), Const(b)) if b != 0 => Const(a % b),
+        (e, Const(n)) if n > 0 && e.is_within(0, n - 1) => e,
+        (e, Const(n)) if n > 0 => match e.split_multiple(n) {
+            Some((_, remainder)) => remainder,
+            None => Mod(Box::new(e), Box::new(Const(n))),
+        },
+        (lhs, rhs) => Mod(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+fn eql(lhs: Expr, rhs: Expr) -> Expr {
+    let ((lhs_low, lhs_high), (rhs_low, rhs_high)) = (lhs.range(), rhs.range());
+    match (lhs, rhs) {
+        (Const(a), Const(b)) => Const((a == b) as i64),
+        _ if lhs_high < rhs_low || rhs_high < lhs_low => Const(0),
+        (lhs, rhs) if lhs == rhs => Const(1),
+        (lhs, rhs) => Eql(Box::new(lhs), Box::new(rhs)),
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Const(n) => write!(f, "{}", n),
+            Digit(i) => write!(f, "d{}", i),
+            Add(lhs, rhs) => write!(f, "({} + {})", lhs, rhs),
+            Mul(lhs, rhs) => write!(f, "({} * {})", lhs, rhs),
+            Div(lhs, rhs) => write!(f, "({} / {})", lhs, rhs),
+            Mod(lhs, rhs) => write!(f, "({} % {})", lhs, rhs),
+            Eql(lhs, rhs) => write!(f, "({} == {})", lhs, rhs),
+        }
+    }
+}
+
+// =====================================================================================
+// Constraints
+// =====================================================================================
+
+/// A sum of input digits multiplied by coefficients, plus a constant.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Linear {
+    coefficients: BTreeMap<usize, i64>,
+    constant: i64,
+}
+
+impl Linear {
+    /// Returns `None` if the expression is not linear in the digits, or if its
+    /// coefficients or range overflow.
+    fn from_expr(expr: &Expr) -> Option<Self> {
+        match expr {
+            Const(n) => Some(Linear {
+                constant: *n,
+                ..Linear::default()
+            }),
+            Digit(i) => Some(Linear {
+                coefficients: BTreeMap::from([(*i, 1)]),
+                constant: 0,
+            }),
+            Add(lhs, rhs) => Linear::from_expr(lhs)?.plus(&Linear::from_expr(rhs)?, 1),
+            Mul(lhs, rhs) => match (&**lhs, &**rhs) {
+                (e, Const(n)) | (Const(n), e) => Linear::default().plus(&Linear::from_expr(e)?, *n),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Adds `other` multiplied by `factor` to this. Returns `None` if that overflows, or
+    /// if the range of the sum does not fit in an i64 when no digits are assigned.
+    fn plus(mut self, other: &Linear, factor: i64) -> Option<Self> {
+        for (&digit, &coefficient) in &other.coefficients {
+            let sum = self.coefficients.entry(digit).or_default();
+            *sum = sum.checked_add(coefficient.checked_mul(factor)?)?;
+        }
+        self.coefficients.retain(|_, coefficient| *coefficient != 0);
+        self.constant = self
+            .constant
+            .checked_add(other.constant.checked_mul(factor)?)?;
+        self.range(&[]).map(|_| self)
+    }
+
+    /// The smallest and largest values given the digits assigned so far, where each
+    /// unassigned digit can be anything from 1 to 9. Returns `None` if they overflow,
+    /// which cannot happen for a `Linear` built by [`Linear::plus`], as assigning digits
+    /// only narrows the range that it checked.
+    fn range(&self, digits: &[Option<i64>]) -> Option<(i64, i64)> {
+        self.coefficients.iter().try_fold(
+            (self.constant, self.constant),
+            |(low, high), (&digit, &coefficient)| {
+                let (a, b) = match digits.get(digit).copied().flatten() {
+                    Some(value) => (value, value),
+                    None => DIGITS,
+                };
+                let (a, b) = (a.checked_mul(coefficient)?, b.checked_mul(coefficient)?);
+                Some((low.checked_add(a.min(b))?, high.checked_add(a.max(b))?))
+            },
+        )
+    }
+}
+
+/// A condition on the input digits that holds along one path through the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Constraint {
+    Zero(Linear),
+    NonZero(Linear),
+}
+
+impl Constraint {
+    fn is_satisfiable(&self, digits: &[Option<i64>]) -> bool {
+        match self {
+            Constraint::Zero(linear) => linear
+                .range(digits)
+                .is_some_and(|(low, high)| low <= 0 && 0 <= high),
+            Constraint::NonZero(linear) => linear.range(digits).is_some_and(|r| r != (0, 0)),
+        }
+    }
+}
+
+/// Displays the constraint as an equation between digits, such as `d5 == d4 + 3`.
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (linear, operator) = match self {
+            Constraint::Zero(linear) => (linear, "=="),
+            Constraint::NonZero(linear) => (linear, "!="),
+        };
+        let side = |sign: i64| {
+            let mut terms: Vec<String> = linear
+                .coefficients
+                .iter()
+                .filter(|(_, &coefficient)| coefficient.signum() == sign)
+                .map(|(digit, &coefficient)| match coefficient.abs() {
+                    1 => format!("d{}", digit),
+                    n => format!("{}*d{}", n, digit),
+                })
+                .collect();
+            if linear.constant.signum() == sign {
+                terms.push(linear.constant.abs().to_string());
+            }
+            match terms.is_empty() {
+                true => String::from("0"),
+                false => terms.join(" + "),
+            }
+        };
+        write!(f, "{} {} {}", side(1), operator, side(-1))
+    }
+}
+
+/// Finds the largest or smallest digits from 1 to 9 that satisfy every constraint.
+fn best_digits(constraints: &[Constraint], digit_count: usize, largest: bool) -> Option<Vec<i64>> {
+    fn search(constraints: &[Constraint], digits: &mut Vec<Option<i64>>, largest: bool) -> bool {
+        return_if!(!constraints.iter().all(|c| c.is_satisfiable(digits)), false);
+        let Some(i) = digits.iter().position(Option::is_none) else {
+            return true;
+        };
+        for n in DIGITS.0..=DIGITS.1 {
+            digits[i] = Some(if largest { DIGITS.0 + DIGITS.1 - n } else { n });
+            return_if!(search(constraints, digits, largest), true);
+        }
+        digits[i] = None;
+        false
+    }
+
+    let mut digits = vec![None; digit_count];
+    search(constraints, &mut digits, largest).then(|| digits.into_iter().flatten().collect())
+}
+
+// =====================================================================================
+// Symbolic execution
+// =====================================================================================
+
+/// A reason that the program could not be solved symbolically.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SymbolicError {
+    /// An expression whose value decides the outcome but is not linear in the digits, or
+    /// whose linear form overflows.
+    Undecidable(String),
+    /// An instruction that writes to a literal instead of a register.
+    InvalidInstruction(usize),
+    NoValidNumber,
+}
+
+impl fmt::Display for SymbolicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolicError::Undecidable(expr) => write!(f, "cannot decide {}", expr),
+            SymbolicError::InvalidInstruction(i) => {
+                write!(f, "instruction {} writes to a literal", i)
+            }
+            SymbolicError::NoValidNumber => write!(f, "no model number is valid"),
+        }
+    }
+}
+
+impl Error for SymbolicError {}
+
+#[derive(Debug, Clone)]
+struct State {
+    registers: [Expr; 4],
+    next_digit: usize,
+    constraints: Vec<Constraint>,
+}
+
+fn register(var: Var) -> Option<usize> {
+    match var {
+        Var::W => Some(0),
+        Var::X => Some(1),
+        Var::Y => Some(2),
+        Var::Z => Some(3),
+        Var::Lit(_) => None,
+    }
+}
+
+impl State {
+    fn value(&self, var: Var) -> Expr {
+        match register(var) {
+            Some(i) => self.registers[i].clone(),
+            None => Const(match var {
+                Var::Lit(n) => n,
+                _ => unreachable!(),
+            }),
+        }
+    }
+
+    /// Adds the constraint if it can still be satisfied along with the others.
+    fn assume(mut self, constraint: Constraint, digit_count: usize) -> Option<Self> {
+        self.constraints.push(constraint);
+        best_digits(&self.constraints, digit_count, true).map(|_| self)
+    }
+}
+
+/// Runs the remaining instructions from the state, collecting the constraints of every
+/// path on which `z` ends at zero.
+fn explore(
+    mut state: State,
+    instructions: &[Instruction],
+    start: usize,
+    digit_count: usize,
+    paths: &mut Vec<Vec<Constraint>>,
+) -> Result<(), SymbolicError> {
+    for (i, &instruction) in instructions.iter().enumerate().skip(start) {
+        let (target, value) = match instruction {
+            Instruction::Inp(v) => {
+                state.next_digit += 1;
+                (v, Digit(state.next_digit - 1))
+            }
+            Instruction::Add(lhs, rhs) => (lhs, add(state.value(lhs), state.value(rhs))),
+            Instruction::Mul(lhs, rhs) => (lhs, mul(state.value(lhs), state.value(rhs))),
+            Instruction::Div(lhs, rhs) => (lhs, div(state.value(lhs), state.value(rhs))),
+            Instruction::Mod(lhs, rhs) => (lhs, modulo(state.value(lhs), state.value(rhs))),
+            Instruction::Eql(lhs, rhs) => (lhs, eql(state.value(lhs), state.value(rhs))),
+        };
+        let target = register(target).ok_or(SymbolicError::InvalidInstruction(i))?;
+
+        if let Eql(lhs, rhs) = &value {
+            let difference = match (Linear::from_expr(lhs), Linear::from_expr(rhs)) {
+                (Some(lhs), Some(rhs)) => lhs.plus(&rhs, -1),
+                _ => None,
+            };
+            let difference =
+                difference.ok_or_else(|| SymbolicError::Undecidable(value.to_string()))?;
+            let outcomes = [
+                (Constraint::Zero(difference.clone()), 1),
+                (Constraint::NonZero(difference), 0),
+            ];
+            for (constraint, outcome) in outcomes {
+                if let Some(mut state) = state.clone().assume(constraint, digit_count) {
+                    state.registers[target] = Const(outcome);
+                    explore(state, instructions, i + 1, digit_count, paths)?;
+                }
+            }
+            return Ok(());
+        }
+        state.registers[target] = value;
+    }
+
+    let z = &state.registers[3];
+    let (low, high) = z.range();
+    if *z == Const(0) {
+        paths.push(state.constraints);
+    } else if low <= 0 && 0 <= high {
+        let z = Linear::from_expr(z).ok_or_else(|| SymbolicError::Undecidable(z.to_string()))?;
+        if let Some(state) = state.assume(Constraint::Zero(z), digit_count) {
+            paths.push(state.constraints);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the constraints on the input digits along every path through the program
+/// that leaves `z` at zero.
+pub fn valid_paths(instructions: &[Instruction]) -> Result<Vec<Vec<Constraint>>, SymbolicError> {
+    let state = State {
+        registers: [Const(0), Const(0), Const(0), Const(0)],
+        next_digit: 0,
+        constraints: Vec::new(),
+    };
+    let digit_count = instructions
+        .iter()
+        .filter(|instruction| matches!(instruction, Instruction::Inp(_)))
+        .count();
+    let mut paths = Vec::new();
+    explore(state, instructions, 0, digit_count, &mut paths)?;
+    Ok(paths)
+}
+
+/// Returns the largest or smallest model number that the program accepts.
+pub fn model_number(instructions: &[Instruction], largest: bool) -> Result<i64, SymbolicError> {
+    let digit_count = instructions
+        .iter()
+        .filter(|instruction| matches!(instruction, Instruction::Inp(_)))
+        .count();
+    let numbers = valid_paths(instructions)?
+        .into_iter()
+        .filter_map(|constraints| best_digits(&constraints, digit_count, largest))
+        .map(|digits| digits.into_iter().fold(0, |n, digit| n * 10 + digit));
+    let best = if largest {
+        numbers.max()
+    } else {
+        numbers.min()
+    };
+    best.ok_or(SymbolicError::NoValidNumber)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::input_generator;
+    use super::super::test::{monad, EXAMPLE_BLOCKS};
+    use super::*;
+
+    #[test]
+    fn test_simplifies_base_26_stack() {
+        let pushed = add(
+            mul(add(Digit(0), Const(4)), Const(26)),
+            add(Digit(1), Const(7)),
+        );
+        assert_eq!(modulo(pushed.clone(), Const(26)), add(Digit(1), Const(7)));
+        assert_eq!(div(pushed.clone(), Const(26)), add(Digit(0), Const(4)));
+        assert_eq!(div(add(Digit(0), Const(4)), Const(26)), Const(0));
+        assert_eq!(eql(add(Digit(0), Const(10)), Digit(1)), Const(0));
+        assert_eq!(
+            eql(add(Digit(0), Const(-3)), Digit(1)).to_string(),
+            "((d0 + -3) == d1)",
+        );
+    }
+
+    #[test]
+    fn test_does_not_fold_overflow() {
+        assert_eq!(
+            add(Const(i64::MAX), Const(1)).to_string(),
+            "(9223372036854775807 + 1)",
+        );
+        assert_eq!(
+            mul(Const(i64::MAX), Const(2)).to_string(),
+            "(2 * 9223372036854775807)",
+        );
+        assert_eq!(
+            add(add(Digit(0), Const(i64::MAX)), Const(1)).to_string(),
+            "((d0 + 9223372036854775807) + 1)",
+        );
+        assert_eq!(
+            add(add(Digit(0), Const(i64::MAX)), Const(-1)),
+            add(Digit(0), Const(i64::MAX - 1)),
+        );
+
+        assert_eq!(Linear::from_expr(&mul(Digit(0), Const(i64::MAX))), None);
+        assert_eq!(Linear::from_expr(&add(Digit(0), Const(i64::MAX))), None);
+        assert_eq!(
+            Linear::from_expr(&add(mul(Digit(0), Const(i64::MAX / 9)), Digit(0))),
+            None,
+        );
+
+        // Both sides of the `eql` fit, but their difference does not.
+        let instructions = input_generator(
+            "inp w\nmul w 1024819115206086200\ninp x\nmul x -1024819115206086200\nadd w x\n\
+             inp y\nmul y 1024819115206086200\ninp z\nmul z -1024819115206086200\nadd y z\n\
+             eql w y",
+        )
+        .unwrap();
+        assert!(matches!(
+            model_number(&instructions, true),
+            Err(SymbolicError::Undecidable(_)),
+        ));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let paths = valid_paths(&monad(&EXAMPLE_BLOCKS)).unwrap();
+        let equalities: Vec<Vec<String>> = paths
+            .iter()
+            .map(|constraints| {
+                constraints
+                    .iter()
+                    .filter(|constraint| matches!(constraint, Constraint::Zero(_)))
+                    .map(Constraint::to_string)
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            equalities,
+            [[
+                "d1 + 6 == d2",
+                "d0 == d3 + 5",
+                "d7 == d8 + 1",
+                "d6 + 3 == d9",
+                "d10 == d11 + 1",
+                "d5 == d12 + 1",
+                "d4 + 6 == d13",
+            ]],
+        );
+    }
+
+    #[test]
+    fn test_model_numbers() {
+        let instructions = monad(&EXAMPLE_BLOCKS);
+        assert_eq!(model_number(&instructions, true), Ok(93943969899889));
+        assert_eq!(model_number(&instructions, false), Ok(61711212142117));
+
+        let instructions = monad(&[
+            (1, 13, 1),
+            (26, -5, 15),
+            (1, 12, 8),
+            (26, -12, 2),
+            (1, 11, 6),
+            (26, -10, 4),
+            (1, 13, 12),
+            (26, -16, 16),
+            (1, 11, 8),
+            (26, -16, 13),
+            (1, 10, 4),
+            (26, -6, 8),
+            (1, 11, 1),
+            (26, -3, 7),
+        ]);
+        assert_eq!(model_number(&instructions, true), Ok(95959595919797));
+        assert_eq!(model_number(&instructions, false), Ok(51515151913131));
+    }
+
+    #[test]
+    fn test_unsolvable_programs() {
+        let instructions = monad(&[(1, 10, 1), (26, -15, 1)]);
+        assert_eq!(
+            model_number(&instructions, true),
+            Err(SymbolicError::NoValidNumber),
+        );
+    }
+}
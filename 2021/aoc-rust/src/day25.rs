@@ -1,67 +1,57 @@
+use aoc_common::{grid::Grid, parse::ParseError};
 use aoc_runner_derive::*;
 
-type Input = Vec<Vec<u8>>;
+type Input = Grid<u8>;
 type Output = usize;
 
 #[aoc_generator(day25, part1, nordzilla)]
 #[aoc_generator(day25, part2, nordzilla)]
-fn input_generator(raw_input: &str) -> Input {
-    raw_input
-        .trim()
-        .lines()
-        .map(|line| line.trim().bytes().collect())
-        .collect()
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+    Grid::try_parse_from_str(raw_input.trim_end(), "'>', 'v', or '.'", |byte| {
+        matches!(byte, b'>' | b'v' | b'.').then_some(byte)
+    })
 }
 
-fn step_right(grid: &mut Vec<Vec<u8>>) -> usize {
-    let mut moved = 0;
-    for row in grid {
-        let width = row.len();
-        for col in 0..width {
-            if b'>' == row[col] {
-                if b'.' == row[(col + 1) % width] {
-                    moved += 1;
-                    row[col] = b'x';
-                    row[(col + 1) % width] = b'<';
-                }
+/// Moves every cucumber of the given herd that can move one spot in the given direction,
+/// marking the spot it left with `x` and the spot it moved to with `moved`, so that no
+/// cucumber moves twice or into a spot that was vacated during the same step.
+fn step_herd(grid: &mut Input, herd: u8, direction: (isize, isize), moved: u8) -> usize {
+    let mut moved_count = 0;
+    for point in grid.points() {
+        if herd == grid[point] {
+            let next = grid.wrapping_offset(point, direction);
+            if let Some(next) = next.filter(|&next| b'.' == grid[next]) {
+                moved_count += 1;
+                grid[point] = b'x';
+                grid[next] = moved;
             }
         }
     }
-    moved
+    moved_count
 }
 
-fn step_down(grid: &mut Vec<Vec<u8>>) -> usize {
-    let mut moved = 0;
-    let height = grid.len();
-    for row in 0..height {
-        for col in 0..grid[row].len() {
-            if b'v' == grid[row][col] {
-                if b'.' == grid[(row + 1) % height][col] {
-                    moved += 1;
-                    grid[row][col] = b'x';
-                    grid[(row + 1) % height][col] = b'^';
-                }
-            }
-        }
-    }
-    moved
+fn step_right(grid: &mut Input) -> usize {
+    step_herd(grid, b'>', (0, 1), b'<')
 }
 
-fn swippy_swappy(grid: &mut Vec<Vec<u8>>) -> usize {
-    for row in grid {
-        for cell in row {
-            match cell {
-                b'<' => *cell = b'>',
-                b'^' => *cell = b'v',
-                b'x' => *cell = b'.',
-                _ => (),
-            }
+fn step_down(grid: &mut Input) -> usize {
+    step_herd(grid, b'v', (1, 0), b'^')
+}
+
+fn swippy_swappy(grid: &mut Input) -> usize {
+    for point in grid.points() {
+        let cell = &mut grid[point];
+        match cell {
+            b'<' => *cell = b'>',
+            b'^' => *cell = b'v',
+            b'x' => *cell = b'.',
+            _ => (),
         }
     }
     0
 }
 
-fn step(grid: &mut Vec<Vec<u8>>) -> usize {
+fn step(grid: &mut Input) -> usize {
     step_right(grid) + swippy_swappy(grid) + step_down(grid) + swippy_swappy(grid)
 }
 
@@ -0,0 +1,6 @@
+use aoc_rust_2024::{SOLUTIONS, inputs};
+
+fn main() {
+  let history = concat!(env!("CARGO_MANIFEST_DIR"), "/bench-history.json");
+  aoc_common::bench::main(2024, SOLUTIONS, &inputs(), history.as_ref());
+}
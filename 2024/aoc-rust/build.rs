@@ -0,0 +1,4 @@
+fn main() {
+  aoc_common::registry::generate().unwrap();
+  aoc_common::examples::generate_tests("../../examples/2024").unwrap();
+}
@@ -1,21 +1,21 @@
+use aoc_common::parse::ParseError;
 use aoc_runner_derive::*;
-use text_io::scan;
 
 type Input = String;
 type Output = u64;
 
 #[aoc_generator(day2, part1, Original)]
 #[aoc_generator(day2, part2, Original)]
-fn input_generator(raw_input: &str) -> Input {
-    raw_input.to_string()
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+  Ok(raw_input.to_string())
 }
 
 #[aoc(day2, part1, Original)]
 fn solve_part1(_: &Input) -> Output {
-    0
+  0
 }
 
 #[aoc(day2, part2, Original)]
 fn solve_part2(_: &Input) -> Output {
-    0
+  0
 }
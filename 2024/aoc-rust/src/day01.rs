@@ -1,33 +1,26 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
-use text_io::scan;
 
 type Input = (Vec<u64>, Vec<u64>);
 type Output = u64;
 
 #[aoc_generator(day1, part1, Original)]
 #[aoc_generator(day1, part2, Original)]
-fn input_generator(raw_input: &str) -> Input {
-  let (mut list1, mut list2) = raw_input
-    .lines()
-    .map(|line| {
-      let value1;
-      let value2;
-      scan!(line.bytes() => "{} {}", value1, value2);
-      (value1, value2)
-    })
-    .fold(
-      (Vec::new(), Vec::new()),
-      |(mut list1, mut list2), (value1, value2)| {
-        list1.push(value1);
-        list2.push(value2);
-        (list1, list2)
-      },
-    );
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
+  let (mut list1, mut list2) = (Vec::new(), Vec::new());
+  for line in raw_input.lines() {
+    let values: Vec<&str> = line.split_ascii_whitespace().collect();
+    let [value1, value2] = values[..] else {
+      return Err(ParseError::new(raw_input, line, "two location IDs"));
+    };
+    list1.push(parse::value(raw_input, value1, "a location ID")?);
+    list2.push(parse::value(raw_input, value2, "a location ID")?);
+  }
 
   list1.sort();
   list2.sort();
 
-  (list1, list2)
+  Ok((list1, list2))
 }
 
 #[aoc(day1, part1, Original)]
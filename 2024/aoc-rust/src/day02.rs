@@ -1,3 +1,4 @@
+use aoc_common::parse::{self, ParseError};
 use aoc_runner_derive::*;
 
 type Input = Vec<Vec<i64>>;
@@ -5,13 +6,13 @@ type Output = usize;
 
 #[aoc_generator(day2, part1, Original)]
 #[aoc_generator(day2, part2, Original)]
-fn input_generator(raw_input: &str) -> Input {
+fn input_generator(raw_input: &str) -> Result<Input, ParseError> {
   raw_input
     .lines()
     .map(|line| {
       line
         .split_ascii_whitespace()
-        .map(|num| num.parse().unwrap())
+        .map(|num| parse::value(raw_input, num, "a level"))
         .collect()
     })
     .collect()
@@ -1,5 +1,7 @@
-#![feature(let_chains)]
-
+use aoc_common::{
+  answers::{Answers, AnswersError},
+  input::Store,
+};
 use aoc_runner_derive::aoc_lib;
 
 pub mod day01;
@@ -29,3 +31,24 @@ pub mod day02;
 //pub mod day25;
 
 aoc_lib! { year = 2024 }
+
+include!(concat!(env!("OUT_DIR"), "/solutions.rs"));
+
+/// The directory of this crate's manifest, which `aoc new` adds days to.
+pub const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+/// The puzzle inputs for this crate, which live in the workspace's gitignored `input`
+/// directory alongside every other year's, just as the examples live in `examples`.
+pub fn inputs() -> Store {
+  Store::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../../input"))
+}
+
+/// The known answers to this crate's puzzles, from its `answers.toml`.
+pub fn answers() -> Result<Answers, AnswersError> {
+  Answers::load(concat!(env!("CARGO_MANIFEST_DIR"), "/answers.toml"))
+}
+
+#[cfg(test)]
+mod examples {
+  include!(concat!(env!("OUT_DIR"), "/examples.rs"));
+}
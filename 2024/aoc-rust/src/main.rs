@@ -1,3 +1,22 @@
-use aoc_runner_derive::aoc_main;
+use aoc_common::runner;
+use aoc_rust_2024::{SOLUTIONS, answers, inputs};
+use std::{env, process};
 
-aoc_main! { lib = aoc_rust_2024 }
+fn main() {
+  match env::args().nth(1).as_deref() {
+    None => runner::run_all(2024, SOLUTIONS, &inputs()),
+    Some("verify") => {
+      let answers = answers().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+      });
+      if !runner::verify_all(2024, SOLUTIONS, &inputs(), &answers) {
+        process::exit(1);
+      }
+    }
+    Some(_) => {
+      eprintln!("usage: aoc-rust-2024 [verify]");
+      process::exit(2);
+    }
+  }
+}
@@ -0,0 +1,346 @@
+//! The known answers to a year's puzzles, which solutions are verified against.
+//!
+//! A year crate records its answers in `answers.toml`, beside its manifest, with a table
+//! for each day:
+//!
+//! ```toml
+//! [day01]
+//! input = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+//! part1 = 1154
+//! part2 = "1127"
+//! ```
+//!
+//! Answers are only right for the input that they were found with, so a day may record
+//! the SHA-256 of that input, as printed by `aoc input import`. Every variant of a part is
+//! expected to give the same answer, which may be written as an integer or as a string.
+//!
+//! A variant that answers differently, such as one that reads the letters of a drawing
+//! another way, may have its own answer in a table for the part, keyed by the variant's
+//! name in any case. The part's own answer is then optional, and applies to every other
+//! variant:
+//!
+//! ```toml
+//! [day13.part2]
+//! answer = "PGHZBFJC"
+//! ascii = "#### ..."
+//! ```
+//!
+//! A day or a part may also record why its solutions should not be checked, such as when
+//! they only handle the shape of the puzzle input, with `ignore = "<reason>"` in its table.
+
+use crate::input::Checksum;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The answers recorded for a single part of a day's puzzle.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Part {
+    answer: Option<String>,
+    ignore: Option<String>,
+    /// The answers of variants that differ from the part's, keyed by lowercase name.
+    variants: BTreeMap<String, String>,
+}
+
+/// The answers recorded for a single day.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Day {
+    input: Option<Checksum>,
+    ignore: Option<String>,
+    parts: BTreeMap<u32, Part>,
+}
+
+/// Reads an answer, which may be written as an integer or as a string.
+fn answer(value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(answer) => Some(answer),
+        toml::Value::Integer(answer) => Some(answer.to_string()),
+        _ => None,
+    }
+}
+
+/// Why an answers file could not be read.
+#[derive(Debug)]
+pub enum AnswersError {
+    /// The file exists but could not be read.
+    Io(PathBuf, io::Error),
+    /// The file is not valid TOML.
+    Toml(PathBuf, toml::de::Error),
+    /// A key in the file is not one that an answers file may have, or its value has the
+    /// wrong type.
+    Invalid(PathBuf, String, &'static str),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            AnswersError::Toml(path, e) => write!(f, "{}: {}", path.display(), e),
+            AnswersError::Invalid(path, key, expected) => {
+                write!(f, "{}: `{}` should be {}", path.display(), key, expected)
+            }
+        }
+    }
+}
+
+impl Error for AnswersError {}
+
+/// The recorded answers for every day of a year.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    days: BTreeMap<u32, Day>,
+}
+
+impl Answers {
+    /// Reads an answers file, treating a file that does not exist as one that records no
+    /// answers.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AnswersError> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(path, &text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(AnswersError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    /// Parses the contents of the answers file at `path`.
+    pub fn parse(path: &Path, text: &str) -> Result<Self, AnswersError> {
+        let invalid =
+            |key: String, expected| AnswersError::Invalid(path.to_path_buf(), key, expected);
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e| AnswersError::Toml(path.to_path_buf(), e))?;
+
+        let mut days = BTreeMap::new();
+        for (name, entries) in table {
+            let day = match name.strip_prefix("day").map(str::parse) {
+                Some(Ok(day @ 1..=25)) => day,
+                _ => return Err(invalid(name, "a table named day01 to day25")),
+            };
+            let toml::Value::Table(entries) = entries else {
+                return Err(invalid(name, "a table"));
+            };
+            let mut answers = Day::default();
+            for (key, value) in entries {
+                let part = match (key.as_str(), value) {
+                    ("input", toml::Value::String(checksum)) => {
+                        let checksum = checksum.parse().map_err(|()| {
+                            invalid(format!("{}.input", name), "a SHA-256 in hexadecimal")
+                        })?;
+                        answers.input = Some(checksum);
+                        continue;
+                    }
+                    ("input", _) => {
+                        return Err(invalid(format!("{}.input", name), "a string"));
+                    }
+                    ("ignore", toml::Value::String(reason)) => {
+                        answers.ignore = Some(reason);
+                        continue;
+                    }
+                    ("ignore", _) => {
+                        return Err(invalid(format!("{}.ignore", name), "a string"));
+                    }
+                    (_, toml::Value::Table(variants)) => {
+                        let mut part = Part::default();
+                        for (variant, value) in variants {
+                            let value = match (variant.as_str(), value) {
+                                ("ignore", toml::Value::String(reason)) => {
+                                    part.ignore = Some(reason);
+                                    continue;
+                                }
+                                ("ignore", _) => {
+                                    return Err(invalid(
+                                        format!("{}.{}.ignore", name, key),
+                                        "a string",
+                                    ));
+                                }
+                                (_, value) => value,
+                            };
+                            let answer = answer(value).ok_or_else(|| {
+                                invalid(
+                                    format!("{}.{}.{}", name, key, variant),
+                                    "an integer or a string",
+                                )
+                            })?;
+                            match variant.as_str() {
+                                "answer" => part.answer = Some(answer),
+                                _ => {
+                                    part.variants.insert(variant.to_lowercase(), answer);
+                                }
+                            }
+                        }
+                        part
+                    }
+                    (_, value) => Part {
+                        answer: Some(answer(value).ok_or_else(|| {
+                            invalid(
+                                format!("{}.{}", name, key),
+                                "an integer, a string, or a table",
+                            )
+                        })?),
+                        ..Part::default()
+                    },
+                };
+                match key.strip_prefix("part").map(str::parse) {
+                    Some(Ok(number @ (1 | 2))) => answers.parts.insert(number, part),
+                    _ => {
+                        return Err(invalid(
+                            format!("{}.{}", name, key),
+                            "input, ignore, part1, or part2",
+                        ))
+                    }
+                };
+            }
+            days.insert(day, answers);
+        }
+        Ok(Self { days })
+    }
+
+    /// The answer that a variant of a part of a day's puzzle should give, if one is
+    /// recorded: the variant's own answer if it has one, or else the part's.
+    pub fn expected(&self, day: u32, part: u32, variant: Option<&str>) -> Option<&str> {
+        let part = self.days.get(&day)?.parts.get(&part)?;
+        variant
+            .and_then(|variant| part.variants.get(&variant.to_lowercase()))
+            .or(part.answer.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Why a part of a day's puzzle should not be checked, if the part or its day records
+    /// a reason.
+    pub fn ignored(&self, day: u32, part: u32) -> Option<&str> {
+        let day = self.days.get(&day)?;
+        day.parts
+            .get(&part)
+            .and_then(|part| part.ignore.as_ref())
+            .or(day.ignore.as_ref())
+            .map(String::as_str)
+    }
+
+    /// The checksum of the input that a day's answers were found with, if one is recorded.
+    pub fn input(&self, day: u32) -> Option<Checksum> {
+        self.days.get(&day)?.input
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Answers, String> {
+        Answers::parse(Path::new("answers.toml"), text).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn reads_answers() {
+        let answers = parse(
+            r#"
+            [day01]
+            input = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            part1 = 1154
+            part2 = "1127"
+
+            [day13]
+            part2 = "PGHZBFJC"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(answers.expected(1, 1, None), Some("1154"));
+        assert_eq!(answers.expected(1, 2, None), Some("1127"));
+        assert_eq!(answers.expected(1, 2, Some("Clever")), Some("1127"));
+        assert_eq!(answers.expected(13, 1, None), None);
+        assert_eq!(answers.expected(13, 2, None), Some("PGHZBFJC"));
+        assert_eq!(answers.expected(2, 1, None), None);
+        assert_eq!(answers.input(1), Some(Checksum::of(b"abc")));
+        assert_eq!(answers.input(13), None);
+    }
+
+    #[test]
+    fn reads_variant_answers() {
+        let answers = parse(
+            r##"
+            [day01.part2]
+            answer = 5
+            clever = 6
+
+            [day13.part2]
+            ascii = "#.#"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(answers.expected(1, 2, None), Some("5"));
+        assert_eq!(answers.expected(1, 2, Some("Clever")), Some("6"));
+        assert_eq!(answers.expected(1, 2, Some("other")), Some("5"));
+        assert_eq!(answers.expected(1, 1, Some("Clever")), None);
+        assert_eq!(answers.expected(13, 2, Some("ascii")), Some("#.#"));
+        assert_eq!(answers.expected(13, 2, None), None);
+    }
+
+    #[test]
+    fn reads_reasons_to_ignore() {
+        let answers = parse(
+            r#"
+            [day03]
+            ignore = "reads 12-bit numbers"
+            part1 = 198
+
+            [day13]
+            part1 = 17
+            part2.ignore = "draws letters"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(answers.ignored(3, 1), Some("reads 12-bit numbers"));
+        assert_eq!(answers.ignored(3, 2), Some("reads 12-bit numbers"));
+        assert_eq!(answers.expected(3, 1, None), Some("198"));
+        assert_eq!(answers.ignored(13, 1), None);
+        assert_eq!(answers.ignored(13, 2), Some("draws letters"));
+        assert_eq!(answers.expected(13, 2, None), None);
+        assert_eq!(answers.ignored(1, 1), None);
+        assert_eq!(
+            parse("[day01]\nignore = 1").unwrap_err(),
+            "answers.toml: `day01.ignore` should be a string",
+        );
+    }
+
+    #[test]
+    fn missing_files_record_nothing() {
+        let path = std::env::temp_dir().join("aoc-answers-missing.toml");
+        assert_eq!(Answers::load(path).unwrap(), Answers::default());
+    }
+
+    #[test]
+    fn rejects_malformed_answers() {
+        assert_eq!(
+            parse("[day26]\npart1 = 1").unwrap_err(),
+            "answers.toml: `day26` should be a table named day01 to day25",
+        );
+        assert_eq!(
+            parse("day01 = 1").unwrap_err(),
+            "answers.toml: `day01` should be a table",
+        );
+        assert_eq!(
+            parse("[day01]\npart3 = 1").unwrap_err(),
+            "answers.toml: `day01.part3` should be input, ignore, part1, or part2",
+        );
+        assert_eq!(
+            parse("[day01]\npart1 = 1.5").unwrap_err(),
+            "answers.toml: `day01.part1` should be an integer, a string, or a table",
+        );
+        assert_eq!(
+            parse("[day01.part1]\nclever = true").unwrap_err(),
+            "answers.toml: `day01.part1.clever` should be an integer or a string",
+        );
+        assert_eq!(
+            parse("[day01]\ninput = \"ba78\"").unwrap_err(),
+            "answers.toml: `day01.input` should be a SHA-256 in hexadecimal",
+        );
+        assert!(parse("[day01\n").unwrap_err().starts_with("answers.toml: "));
+    }
+}
@@ -0,0 +1,370 @@
+//! Benchmarks every solution's generator and solver, keeping a history of the timings so
+//! that a run can be compared with the one before it.
+//!
+//! The history is a JSON file of every run's mean time per iteration for each benchmark.
+//! Each run is reported against the last recorded run, and a benchmark whose time grew by
+//! more than the threshold is flagged as a regression.
+//!
+//! With the `bench` feature, a year crate's Criterion benchmark target is just:
+//!
+//! ```ignore
+//! fn main() {
+//!     let history = concat!(env!("CARGO_MANIFEST_DIR"), "/bench-history.json");
+//!     aoc_common::bench::main(2021, SOLUTIONS, &inputs(), history.as_ref());
+//! }
+//! ```
+
+use crate::registry::Solution;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The regression threshold, in percent, when `AOC_BENCH_THRESHOLD` is not set.
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// The timings from one benchmark run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    /// When the run finished, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The mean time per iteration of each benchmark, in nanoseconds.
+    pub timings: BTreeMap<String, f64>,
+}
+
+/// Every recorded benchmark run, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub runs: Vec<Run>,
+}
+
+/// Why the history file could not be read or written.
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            HistoryError::Json(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl Error for HistoryError {}
+
+impl History {
+    /// Reads a history file, treating a file that does not exist as an empty history.
+    pub fn load(path: &Path) -> Result<Self, HistoryError> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                serde_json::from_str(&text).map_err(|e| HistoryError::Json(path.to_path_buf(), e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(HistoryError::Io(path.to_path_buf(), e)),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), HistoryError> {
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(path, json + "\n").map_err(|e| HistoryError::Io(path.to_path_buf(), e))
+    }
+
+    /// The most recent run that timed the given benchmark.
+    pub fn last_timing(&self, name: &str) -> Option<(u64, f64)> {
+        self.runs
+            .iter()
+            .rev()
+            .find_map(|run| Some((run.timestamp, *run.timings.get(name)?)))
+    }
+}
+
+/// How a benchmark's time changed since the last run that recorded it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub name: String,
+    /// The previous time in nanoseconds, or `None` if this is the first time it was run.
+    pub before: Option<f64>,
+    pub after: f64,
+}
+
+impl Change {
+    /// The change in time as a percentage of the previous time.
+    pub fn percent(&self) -> Option<f64> {
+        self.before
+            .map(|before| (self.after - before) / before * 100.0)
+    }
+
+    /// Whether the time grew by more than `threshold` percent.
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        self.percent().is_some_and(|percent| percent > threshold)
+    }
+}
+
+/// Compares each timing in a run with the most recent timing of the same benchmark that
+/// is already in the history.
+pub fn compare(history: &History, run: &Run) -> Vec<Change> {
+    run.timings
+        .iter()
+        .map(|(name, &after)| Change {
+            name: name.clone(),
+            before: history.last_timing(name).map(|(_, before)| before),
+            after,
+        })
+        .collect()
+}
+
+fn nanos(time: f64) -> Duration {
+    Duration::from_nanos(time.round() as u64)
+}
+
+/// Renders a table of changes, flagging every regression beyond `threshold` percent.
+pub fn report(changes: &[Change], threshold: f64) -> String {
+    let width = changes.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut report = String::new();
+    for change in changes {
+        let before = match change.before {
+            Some(before) => format!("{:.1?}", nanos(before)),
+            None => "-".to_string(),
+        };
+        let percent = match change.percent() {
+            Some(percent) => format!("{:+.1}%", percent),
+            None => "new".to_string(),
+        };
+        let flag = if change.is_regression(threshold) {
+            "  REGRESSED"
+        } else {
+            ""
+        };
+        report += &format!(
+            "{:<width$}  {:>10} -> {:>10}  {:>8}{}\n",
+            change.name,
+            before,
+            format!("{:.1?}", nanos(change.after)),
+            percent,
+            flag,
+            width = width,
+        );
+    }
+    let regressions = changes
+        .iter()
+        .filter(|c| c.is_regression(threshold))
+        .count();
+    report += &format!(
+        "{} of {} benchmarks regressed by more than {}%\n",
+        regressions,
+        changes.len(),
+        threshold
+    );
+    report
+}
+
+/// The name of a solution's benchmarks, such as `day01/part2/clever`.
+pub fn name(solution: &Solution) -> String {
+    match solution.variant {
+        Some(variant) => format!(
+            "day{:02}/part{}/{}",
+            solution.day,
+            solution.part,
+            variant.to_lowercase()
+        ),
+        None => format!("day{:02}/part{}", solution.day, solution.part),
+    }
+}
+
+#[cfg(feature = "bench")]
+pub use criterion_main::main;
+
+#[cfg(feature = "bench")]
+mod criterion_main {
+    use super::*;
+    use crate::input::Store;
+    use aoc_runner::ArcStr;
+    use criterion::{black_box, Bencher, Criterion};
+    use std::{
+        env,
+        time::{Instant, SystemTime},
+    };
+
+    /// Times `routine` for Criterion, adding up the iterations and the time that they took.
+    fn measure<T>(
+        bencher: &mut Bencher,
+        total: &mut (u64, Duration),
+        mut routine: impl FnMut() -> T,
+    ) {
+        bencher.iter_custom(|iters| {
+            let start_time = Instant::now();
+            for _ in 0..iters {
+                black_box(routine());
+            }
+            let time = start_time.elapsed();
+            total.0 += iters;
+            total.1 += time;
+            time
+        });
+    }
+
+    /// Benchmarks the generator and the solver of every solution that has an input, then
+    /// reports how their times changed since the last run and records this run in the
+    /// history file at `history`.
+    ///
+    /// The history is only updated by `cargo bench`, and not when the target runs as a test.
+    /// `AOC_BENCH_THRESHOLD` sets the percentage beyond which a slowdown is a regression.
+    pub fn main(year: u32, solutions: &[Solution], inputs: &Store, history: &Path) {
+        let mut criterion = Criterion::default()
+            .sample_size(10)
+            .warm_up_time(Duration::from_secs(1))
+            .measurement_time(Duration::from_secs(3))
+            .configure_from_args();
+
+        let mut timings = BTreeMap::new();
+        for solution in solutions {
+            let name = name(solution);
+            let input = match inputs.load((year, solution.day)) {
+                Ok(input) => ArcStr::from(&input),
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", name, e);
+                    continue;
+                }
+            };
+            let runner = match (solution.factory)(input.clone()) {
+                Ok(runner) => runner,
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", name, e);
+                    continue;
+                }
+            };
+
+            let mut generator = (0, Duration::ZERO);
+            criterion.bench_function(&format!("{}/generator", name), |b| {
+                measure(b, &mut generator, || (solution.factory)(input.clone()))
+            });
+            let mut solver = (0, Duration::ZERO);
+            criterion.bench_function(&format!("{}/solver", name), |b| {
+                measure(b, &mut solver, || {
+                    runner.try_run().map(|result| result.to_string())
+                })
+            });
+
+            for (stage, (iters, time)) in [("generator", generator), ("solver", solver)] {
+                if iters > 0 {
+                    let mean = time.as_nanos() as f64 / iters as f64;
+                    timings.insert(format!("{}/{}", name, stage), mean);
+                }
+            }
+        }
+        criterion.final_summary();
+
+        if !env::args().any(|arg| arg == "--bench") || timings.is_empty() {
+            return;
+        }
+        let threshold = env::var("AOC_BENCH_THRESHOLD")
+            .ok()
+            .and_then(|threshold| threshold.parse().ok())
+            .unwrap_or(DEFAULT_THRESHOLD);
+        let path = history;
+        let mut history = History::load(path).unwrap_or_else(|e| panic!("{}", e));
+        let run = Run {
+            timestamp: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            timings,
+        };
+        print!("\n{}", report(&compare(&history, &run), threshold));
+        history.runs.push(run);
+        history.save(path).unwrap_or_else(|e| panic!("{}", e));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(timestamp: u64, timings: &[(&str, f64)]) -> Run {
+        Run {
+            timestamp,
+            timings: timings
+                .iter()
+                .map(|&(name, time)| (name.to_string(), time))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn compares_with_the_last_timing() {
+        let history = History {
+            runs: vec![
+                run(
+                    1,
+                    &[
+                        ("day01/part1/generator", 100.0),
+                        ("day01/part1/solver", 200.0),
+                    ],
+                ),
+                run(2, &[("day01/part1/generator", 120.0)]),
+            ],
+        };
+        let changes = compare(
+            &history,
+            &run(
+                3,
+                &[
+                    ("day01/part1/generator", 126.0),
+                    ("day01/part1/solver", 150.0),
+                    ("day02/part1/solver", 10.0),
+                ],
+            ),
+        );
+
+        let percents: Vec<_> = changes.iter().map(|c| c.percent()).collect();
+        assert_eq!(percents, [Some(5.0), Some(-25.0), None]);
+        assert!(changes[0].is_regression(4.0));
+        assert!(!changes[0].is_regression(10.0));
+        assert!(!changes[1].is_regression(0.0));
+        assert!(!changes[2].is_regression(0.0));
+    }
+
+    #[test]
+    fn reports_regressions() {
+        let changes = [
+            Change {
+                name: "day01/part1/solver".to_string(),
+                before: Some(1000.0),
+                after: 1500.0,
+            },
+            Change {
+                name: "day02/part2/generator".to_string(),
+                before: None,
+                after: 2000.0,
+            },
+        ];
+        assert_eq!(
+            report(&changes, 10.0),
+            "day01/part1/solver          1.0µs ->      1.5µs    +50.0%  REGRESSED\n\
+             day02/part2/generator           - ->      2.0µs       new\n\
+             1 of 2 benchmarks regressed by more than 10%\n",
+        );
+    }
+
+    #[test]
+    fn round_trips_history() {
+        let path = std::env::temp_dir().join(format!("aoc-bench-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(History::load(&path).unwrap(), History::default());
+
+        let history = History {
+            runs: vec![run(1, &[("day01/part1/solver", 12.5)])],
+        };
+        history.save(&path).unwrap();
+        assert_eq!(History::load(&path).unwrap(), history);
+        fs::remove_file(&path).unwrap();
+    }
+}
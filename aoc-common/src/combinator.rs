@@ -0,0 +1,373 @@
+//! Parser combinator primitives over slices of any token type.
+//!
+//! A parser is a function that takes the remaining input and returns the parsed value
+//! along with the input that follows it, or a [`Failure`] describing what it expected
+//! to find and where. Parsers are written as plain functions and composed by passing
+//! them to the combinators in this module:
+//!
+//! ```
+//! use aoc_common::combinator::*;
+//!
+//! fn digit(input: &[u8]) -> ParseResult<'_, u8, u8> {
+//!     satisfy("a digit", |byte| byte.is_ascii_digit(), input).map_front(|byte| byte - b'0')
+//! }
+//!
+//! fn pair(input: &[u8]) -> ParseResult<'_, u8, (u8, u8)> {
+//!     let (lhs, input) = digit(input)?;
+//!     let (_, input) = token(b',', input)?;
+//!     let (rhs, input) = digit(input)?;
+//!     Ok(((lhs, rhs), input))
+//! }
+//!
+//! assert_eq!(parse_all(pair, b"1,2"), Ok((1, 2)));
+//!
+//! let input = b"1;2";
+//! let error = parse_all(pair, input).unwrap_err();
+//! assert_eq!(error.expected(), "','");
+//! assert_eq!(error.offset(input), 1);
+//! ```
+
+use crate::parse;
+use std::{cmp::Ordering, error::Error, fmt};
+
+/// The parsed value together with the input that remains after it, or the reason that
+/// parsing failed.
+pub type ParseResult<'a, I, T> = Result<(T, &'a [I]), Failure>;
+
+/// Describes where a parser failed and what it expected to find there.
+///
+/// A failure only knows how much of the parser's input was left, so it is not an error to
+/// report on its own: [`Failure::locate`] turns it into a [`parse::ParseError`], which
+/// gives the line and column within the puzzle input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The count of input tokens that had not been consumed when parsing failed.
+    remaining: usize,
+    expected: String,
+}
+
+impl Failure {
+    /// Creates an error for a parser that expected something else at the start of `input`.
+    pub fn new<I>(expected: impl Into<String>, input: &[I]) -> Self {
+        Self {
+            remaining: input.len(),
+            expected: expected.into(),
+        }
+    }
+
+    /// A description of what the parser expected to find.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// The offset of the failure within the full input that was given to the parser.
+    pub fn offset<I>(&self, input: &[I]) -> usize {
+        input.len().saturating_sub(self.remaining)
+    }
+
+    /// Adjusts an error from a parser that was run on a sub-slice of the input, so that its
+    /// offset is measured against the input that continues with `rest` after the sub-slice.
+    pub fn followed_by<I>(self, rest: &[I]) -> Self {
+        Self {
+            remaining: self.remaining + rest.len(),
+            ..self
+        }
+    }
+
+    /// Locates the error within the puzzle input, given the slice of `input` that the
+    /// parser was run on.
+    pub fn locate(&self, input: &str, text: &str) -> parse::ParseError {
+        let offset = parse::offset_of(input, text) + self.offset(text.as_bytes());
+        parse::ParseError::at(input, offset, &*self.expected)
+    }
+
+    /// Returns whichever of two errors occurred further into the input, or combines
+    /// what they expected if they occurred at the same position.
+    fn furthest(self, other: Self) -> Self {
+        match other.remaining.cmp(&self.remaining) {
+            Ordering::Less => other,
+            Ordering::Greater => self,
+            Ordering::Equal => Self {
+                remaining: self.remaining,
+                expected: format!("{} or {}", self.expected, other.expected),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {}", self.expected)
+    }
+}
+
+impl Error for Failure {}
+
+/// Transforms the parsed value of a successful result, leaving the remaining input as is.
+pub trait MapFront<Front, Mapped> {
+    type Output;
+    fn map_front(self, f: impl FnOnce(Front) -> Mapped) -> Self::Output;
+}
+
+impl<Front, Mapped, Back, E> MapFront<Front, Mapped> for Result<(Front, Back), E> {
+    type Output = Result<(Mapped, Back), E>;
+    fn map_front(self, f: impl FnOnce(Front) -> Mapped) -> Self::Output {
+        self.map(|(front, back)| (f(front), back))
+    }
+}
+
+/// Parses any single token.
+pub fn next<I: Copy>(input: &[I]) -> ParseResult<'_, I, I> {
+    match input.split_first() {
+        Some((&first, rest)) => Ok((first, rest)),
+        None => Err(Failure::new("more input", input)),
+    }
+}
+
+/// Parses exactly `n` tokens.
+pub fn take<I>(n: usize, input: &[I]) -> ParseResult<'_, I, &[I]> {
+    if n <= input.len() {
+        Ok(input.split_at(n))
+    } else {
+        Err(Failure::new(format!("{} more tokens", n), input))
+    }
+}
+
+/// Parses a single token that matches the predicate.
+pub fn satisfy<'a, I: Copy>(
+    expected: &str,
+    predicate: impl Fn(I) -> bool,
+    input: &'a [I],
+) -> ParseResult<'a, I, I> {
+    next(input)
+        .ok()
+        .filter(|&(token, _)| predicate(token))
+        .ok_or_else(|| Failure::new(expected, input))
+}
+
+/// Parses a single token that is equal to the expected token.
+pub fn token<I>(expected: I, input: &[I]) -> ParseResult<'_, I, I>
+where
+    I: Copy + PartialEq + Into<char>,
+{
+    satisfy(&format!("{:?}", expected.into()), |token| token == expected, input)
+}
+
+/// Parses the longest run of one or more tokens that match the predicate.
+pub fn take_while<'a, I>(
+    expected: &str,
+    predicate: impl Fn(&I) -> bool,
+    input: &'a [I],
+) -> ParseResult<'a, I, &'a [I]> {
+    let len = input
+        .iter()
+        .position(|token| !predicate(token))
+        .unwrap_or(input.len());
+    if len > 0 {
+        Ok(input.split_at(len))
+    } else {
+        Err(Failure::new(expected, input))
+    }
+}
+
+/// Succeeds without consuming anything if there is no input left.
+pub fn end<I>(input: &[I]) -> ParseResult<'_, I, ()> {
+    if input.is_empty() {
+        Ok(((), input))
+    } else {
+        Err(Failure::new("end of input", input))
+    }
+}
+
+/// Runs the parser, but fails at the position where it started if the parsed value
+/// does not match the predicate.
+pub fn require<'a, I, T>(
+    expected: &str,
+    predicate: impl Fn(&T) -> bool,
+    parse: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    input: &'a [I],
+) -> ParseResult<'a, I, T> {
+    parse(input).and_then(|(parsed, rest)| {
+        if predicate(&parsed) {
+            Ok((parsed, rest))
+        } else {
+            Err(Failure::new(expected, input))
+        }
+    })
+}
+
+/// Runs the first parser, and runs the second parser from the same position if the first fails.
+///
+/// If both fail, returns the error from whichever parser got further into the input.
+pub fn either<'a, I, T>(
+    first: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    second: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    input: &'a [I],
+) -> ParseResult<'a, I, T> {
+    first(input).or_else(|first_error| second(input).map_err(|e| first_error.furthest(e)))
+}
+
+/// Runs the parser exactly `n` times in a row.
+pub fn parse_exactly<'a, I, T>(
+    n: usize,
+    parse: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    mut input: &'a [I],
+) -> ParseResult<'a, I, Vec<T>> {
+    let mut values = Vec::with_capacity(n);
+    while values.len() < n {
+        let (parsed, rest) = parse(input)?;
+        values.push(parsed);
+        input = rest;
+    }
+    Ok((values, input))
+}
+
+/// Runs the parser as many times as it succeeds, failing only if it never succeeds.
+pub fn parse_one_or_more<'a, I, T>(
+    parse: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    input: &'a [I],
+) -> ParseResult<'a, I, Vec<T>> {
+    let (first, mut input) = parse(input)?;
+    let mut values = vec![first];
+    while let Ok((parsed, rest)) = parse(input) {
+        values.push(parsed);
+        input = rest;
+    }
+    Ok((values, input))
+}
+
+/// Runs the parser repeatedly until it produces a value that matches the predicate,
+/// which is included as the last of the parsed values.
+pub fn parse_until<'a, I, T>(
+    predicate: impl Fn(&T) -> bool,
+    parse: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    mut input: &'a [I],
+) -> ParseResult<'a, I, Vec<T>> {
+    let mut values = Vec::new();
+    loop {
+        let (parsed, rest) = parse(input)?;
+        input = rest;
+        let done = predicate(&parsed);
+        values.push(parsed);
+        if done {
+            return Ok((values, input));
+        }
+    }
+}
+
+/// Runs the parser over the whole input, failing if any input is left over.
+pub fn parse_all<'a, I, T>(
+    parse: impl Fn(&'a [I]) -> ParseResult<'a, I, T>,
+    input: &'a [I],
+) -> Result<T, Failure> {
+    let (parsed, rest) = parse(input)?;
+    end(rest)?;
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn digit(input: &[u8]) -> ParseResult<'_, u8, u8> {
+        satisfy("a digit", |byte| byte.is_ascii_digit(), input).map_front(|byte| byte - b'0')
+    }
+
+    fn letter(input: &[u8]) -> ParseResult<'_, u8, u8> {
+        satisfy("a letter", |byte| byte.is_ascii_alphabetic(), input)
+    }
+
+    #[test]
+    fn single_tokens() {
+        assert_eq!(next(b"ab"), Ok((b'a', &b"b"[..])));
+        assert_eq!(next::<u8>(b"").unwrap_err().expected(), "more input");
+        assert_eq!(token(b'a', b"ab"), Ok((b'a', &b"b"[..])));
+        assert_eq!(token(b'[', b"ab").unwrap_err().expected(), "'['");
+        assert_eq!(digit(b"7x"), Ok((7, &b"x"[..])));
+    }
+
+    #[test]
+    fn takes() {
+        assert_eq!(take(2, &[1, 0, 1]), Ok((&[1, 0][..], &[1][..])));
+        assert_eq!(take(4, &[1, 0, 1]).unwrap_err().expected(), "4 more tokens");
+        assert_eq!(
+            take_while("digits", u8::is_ascii_digit, b"123,"),
+            Ok((&b"123"[..], &b","[..])),
+        );
+        assert_eq!(
+            take_while("digits", u8::is_ascii_digit, b"123"),
+            Ok((&b"123"[..], &b""[..])),
+        );
+        assert!(take_while("digits", u8::is_ascii_digit, b",1").is_err());
+    }
+
+    #[test]
+    fn requirements() {
+        let odd = |input| require("an odd digit", |&n| n % 2 == 1, digit, input);
+        assert_eq!(odd(b"3"), Ok((3, &b""[..])));
+        let input = b"4";
+        let error = odd(input).unwrap_err();
+        assert_eq!(error.expected(), "an odd digit");
+        assert_eq!(error.offset(input), 0);
+    }
+
+    #[test]
+    fn alternatives() {
+        assert_eq!(either(digit, letter, b"a"), Ok((b'a', &b""[..])));
+        assert_eq!(either(digit, letter, b"1"), Ok((1, &b""[..])));
+        assert_eq!(
+            either(digit, letter, b"?").unwrap_err().expected(),
+            "a digit or a letter",
+        );
+
+        let two_digits = |input| parse_exactly(2, digit, input).map_front(|_| 0);
+        let input = b"1?";
+        let error = either(two_digits, letter, input).unwrap_err();
+        assert_eq!(error.expected(), "a digit");
+        assert_eq!(error.offset(input), 1);
+    }
+
+    #[test]
+    fn repetition() {
+        assert_eq!(parse_exactly(2, digit, b"123"), Ok((vec![1, 2], &b"3"[..])));
+        assert_eq!(parse_exactly(0, digit, b"123"), Ok((vec![], &b"123"[..])));
+        assert_eq!(parse_exactly(4, digit, b"123").unwrap_err().offset(b"123"), 3);
+
+        assert_eq!(parse_one_or_more(digit, b"12a"), Ok((vec![1, 2], &b"a"[..])));
+        assert!(parse_one_or_more(digit, b"a").is_err());
+
+        assert_eq!(
+            parse_until(|&n| n == 0, digit, b"1200"),
+            Ok((vec![1, 2, 0], &b"0"[..])),
+        );
+        assert_eq!(parse_until(|&n| n == 0, digit, b"12").unwrap_err().offset(b"12"), 2);
+    }
+
+    #[test]
+    fn whole_input() {
+        assert_eq!(parse_all(digit, b"1"), Ok(1));
+        let input = b"12";
+        let error = parse_all(digit, input).unwrap_err();
+        assert_eq!(error.to_string(), "expected end of input");
+        assert_eq!(error.offset(input), 1);
+    }
+
+    #[test]
+    fn sub_slices() {
+        let input = b"12a4";
+        let (head, rest) = take(3, input).unwrap();
+        let error = parse_all(digit, head).unwrap_err().followed_by(rest);
+        assert_eq!(error.offset(input), 1);
+    }
+
+    #[test]
+    fn located_in_puzzle_input() {
+        let input = "12\n3;4";
+        let line = input.lines().nth(1).unwrap();
+        let error = parse_all(digit, line.as_bytes()).unwrap_err();
+        let located = error.locate(input, line);
+        assert_eq!((located.line, located.column), (2, 2));
+        assert_eq!(located.found, ";4");
+        assert_eq!(located.expected, "end of input");
+    }
+}
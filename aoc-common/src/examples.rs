@@ -0,0 +1,229 @@
+//! Tests that run every solution on the example from its puzzle's description.
+//!
+//! Examples live in `examples/<year>/` at the root of the workspace, one per day, as
+//! `dayNN.txt`. When a part's example differs from the rest of the day's, as for packets
+//! of different kinds, it lives in `dayNN-partN.txt` instead. Their answers are recorded
+//! beside them in an `answers.toml` of the same format as a year's own answers.
+//!
+//! A year crate's build script generates a `#[test]` for each solution that has both an
+//! example and an answer, which its library includes. Every other solution gets an
+//! `#[ignore]`d test instead, giving the reason that its answers record or else what is
+//! missing, so that the gaps show up in `cargo test`:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     aoc_common::examples::generate_tests("../../examples/2021").unwrap();
+//! }
+//!
+//! // lib.rs
+//! #[cfg(test)]
+//! mod examples {
+//!     include!(concat!(env!("OUT_DIR"), "/examples.rs"));
+//! }
+//! ```
+
+use crate::{
+    answers::Answers,
+    registry::{self, DayPart, Solution},
+};
+use aoc_runner::ArcStr;
+use std::{
+    env,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The name of the file, directly under the examples directory, that records the answers.
+const ANSWERS: &str = "answers.toml";
+
+/// The example for a part of a day's puzzle, if there is one.
+pub fn path(dir: &Path, day: u32, part: u32) -> Option<PathBuf> {
+    [
+        format!("day{:02}-part{}.txt", day, part),
+        format!("day{:02}.txt", day),
+    ]
+    .into_iter()
+    .map(|name| dir.join(name))
+    .find(|path| path.exists())
+}
+
+/// Runs a solution on its example and panics unless it gives the recorded answer.
+///
+/// Called by the tests that [`generate_tests`] writes.
+pub fn check(dir: &str, solution: Solution) {
+    let dir = Path::new(dir);
+    let (day, part) = (solution.day, solution.part);
+    let answers = Answers::load(dir.join(ANSWERS)).unwrap_or_else(|e| panic!("{}", e));
+    let expected = answers
+        .expected(day, part, solution.variant)
+        .unwrap_or_else(|| panic!("no answer for day {} part {}", day, part));
+    let path =
+        path(dir, day, part).unwrap_or_else(|| panic!("no example for day {} part {}", day, part));
+    let input = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+    let runner = (solution.factory)(ArcStr::from(&input))
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let answer = runner
+        .try_run()
+        .unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+        .to_string();
+    assert_eq!(answer, expected, "the answer to {}", path.display());
+}
+
+/// The name of the test for a solution, such as `day01_part2_clever`.
+fn test_name(day_part: &DayPart) -> String {
+    let DayPart { day, part, variant } = day_part;
+    match variant {
+        Some(variant) => format!("day{:02}_part{}_{}", day, part, variant.to_lowercase()),
+        None => format!("day{:02}_part{}", day, part),
+    }
+}
+
+/// Why a solution's example cannot be checked, if it cannot.
+fn ignore_reason(dir: &Path, day_part: &DayPart, answers: &Answers) -> Option<String> {
+    let (day, part) = (day_part.day, day_part.part);
+    if let Some(reason) = answers.ignored(day, part) {
+        Some(reason.to_string())
+    } else if path(dir, day, part).is_none() {
+        Some("no example".to_string())
+    } else if answers
+        .expected(day, part, day_part.variant.as_deref())
+        .is_none()
+    {
+        Some("no recorded answer".to_string())
+    } else {
+        None
+    }
+}
+
+/// Renders a test for each of the solutions, which is ignored unless the solution has an
+/// example and an answer.
+fn render_tests(dir: &Path, day_parts: &[DayPart], answers: &Answers) -> String {
+    let mut source = String::new();
+    for day_part in day_parts {
+        let ignore = match ignore_reason(dir, day_part, answers) {
+            Some(reason) => format!("#[ignore = {:?}]\n", reason),
+            None => String::new(),
+        };
+        writeln!(
+            source,
+            "#[test]\n{}fn {}() {{\n    aoc_common::examples::check({:?}, {});\n}}\n",
+            ignore,
+            test_name(day_part),
+            dir.display().to_string(),
+            day_part.literal(),
+        )
+        .unwrap();
+    }
+    source
+}
+
+/// Scans the calling crate's library modules and writes `examples.rs` into `OUT_DIR`, with
+/// a test for every solution that checks its example in `dir` against the recorded answer.
+/// A relative `dir` is relative to the calling crate's manifest.
+///
+/// Intended to be called from a year crate's build script.
+pub fn generate_tests(dir: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
+    let dir = manifest_dir.join(dir);
+    let answers = Answers::load(dir.join(ANSWERS))?;
+    let day_parts = registry::scan(&manifest_dir.join("src"))?;
+    let out_file = PathBuf::from(env::var("OUT_DIR")?).join("examples.rs");
+    fs::write(out_file, render_tests(&dir, &day_parts, &answers))?;
+    println!("cargo:rerun-if-changed={}", dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::registry::test::day_part;
+    use std::ops::Deref;
+
+    /// A scratch directory, which is removed when it is dropped.
+    struct ScratchDir(PathBuf);
+
+    impl Deref for ScratchDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A directory of examples that is unique to the calling test.
+    fn scratch_dir(name: &str, files: &[(&str, &str)]) -> ScratchDir {
+        let dir = env::temp_dir().join(format!("aoc-examples-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+        ScratchDir(dir)
+    }
+
+    #[test]
+    fn finds_examples() {
+        let dir = scratch_dir(
+            "finds_examples",
+            &[
+                ("day01.txt", ""),
+                ("day16.txt", ""),
+                ("day16-part2.txt", ""),
+            ],
+        );
+        assert_eq!(path(&dir, 1, 2), Some(dir.join("day01.txt")));
+        assert_eq!(path(&dir, 16, 1), Some(dir.join("day16.txt")));
+        assert_eq!(path(&dir, 16, 2), Some(dir.join("day16-part2.txt")));
+        assert_eq!(path(&dir, 2, 1), None);
+    }
+
+    #[test]
+    fn renders_tests_for_answered_examples() {
+        let dir = scratch_dir("renders_tests", &[("day01.txt", ""), ("day02.txt", "")]);
+        let answers = Answers::parse(
+            Path::new(ANSWERS),
+            "[day01]\npart1 = 7\n[day02]\nignore = \"too small\"\n[day03]\npart1 = 1",
+        )
+        .unwrap();
+        let day_parts = [
+            day_part(1, 1, None),
+            day_part(1, 1, Some("Clever")),
+            day_part(1, 2, None),
+            day_part(2, 1, None),
+            day_part(3, 1, None),
+        ];
+        let source = render_tests(&dir, &day_parts, &answers);
+
+        let tests: Vec<_> = source
+            .split("#[test]\n")
+            .skip(1)
+            .map(|test| test.lines().take_while(|line| !line.starts_with("    ")))
+            .map(|lines| lines.collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(
+            tests,
+            [
+                "fn day01_part1() {",
+                "fn day01_part1_clever() {",
+                "#[ignore = \"no recorded answer\"] fn day01_part2() {",
+                "#[ignore = \"too small\"] fn day02_part1() {",
+                "#[ignore = \"no example\"] fn day03_part1() {",
+            ],
+        );
+        assert!(source.contains(&format!(
+            "aoc_common::examples::check({:?}, {});",
+            dir.display().to_string(),
+            day_part(1, 1, Some("Clever")).literal(),
+        )));
+    }
+}
@@ -0,0 +1,458 @@
+//! Helpers for walking two-dimensional grids addressed by `(row, col)` points.
+
+use crate::parse::ParseError;
+use std::{
+    fmt::{self, Display},
+    ops::{Index, IndexMut},
+};
+
+/// The offsets to the four orthogonally adjacent points: up, right, down, left.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// The offsets to all eight surrounding points, clockwise starting from the top left.
+pub const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+/// Returns an iterator over every point in a `rows` by `cols` rectangle whose top-left
+/// corner is at the given start point, in row-major order.
+pub fn rectangle(
+    (start_row, start_col): (i64, i64),
+    rows: i64,
+    cols: i64,
+) -> impl Iterator<Item = (i64, i64)> {
+    (0..rows).flat_map(move |row| (0..cols).map(move |col| (start_row + row, start_col + col)))
+}
+
+/// Returns an iterator over every point in a grid of the given size, in row-major order.
+pub fn points((rows, cols): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    (0..rows).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+}
+
+/// Returns the point at the given offset from `(row, col)`, or `None` if it would fall
+/// outside of a grid of the given size.
+pub fn offset(
+    (row, col): (usize, usize),
+    (d_row, d_col): (isize, isize),
+    (rows, cols): (usize, usize),
+) -> Option<(usize, usize)> {
+    let row = row.checked_add_signed(d_row).filter(|&row| row < rows)?;
+    let col = col.checked_add_signed(d_col).filter(|&col| col < cols)?;
+    Some((row, col))
+}
+
+/// Returns an iterator over the in-bounds points that are orthogonally adjacent to `point`.
+pub fn orthogonal_neighbors(
+    point: (usize, usize),
+    size: (usize, usize),
+) -> impl Iterator<Item = (usize, usize)> {
+    ORTHOGONAL
+        .into_iter()
+        .filter_map(move |delta| offset(point, delta, size))
+}
+
+/// Returns an iterator over the in-bounds points that surround `point`, including diagonals.
+pub fn surrounding_neighbors(
+    point: (usize, usize),
+    size: (usize, usize),
+) -> impl Iterator<Item = (usize, usize)> {
+    SURROUNDING
+        .into_iter()
+        .filter_map(move |delta| offset(point, delta, size))
+}
+
+/// Returns the point at the given offset from `(row, col)` in a grid of the given size,
+/// wrapping around the edges as if the grid were the surface of a torus, or `None` if the
+/// grid is empty.
+pub fn wrapping_offset(
+    (row, col): (usize, usize),
+    (d_row, d_col): (isize, isize),
+    (rows, cols): (usize, usize),
+) -> Option<(usize, usize)> {
+    let row = (row as isize + d_row).checked_rem_euclid(rows as isize)?;
+    let col = (col as isize + d_col).checked_rem_euclid(cols as isize)?;
+    Some((row as usize, col as usize))
+}
+
+/// A rectangular grid of cells stored contiguously in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid of the given size by calling `f` with each point in row-major order.
+    pub fn from_fn(size: (usize, usize), f: impl FnMut((usize, usize)) -> T) -> Self {
+        Self {
+            rows: size.0,
+            cols: size.1,
+            cells: points(size).map(f).collect(),
+        }
+    }
+
+    /// Creates a grid from a list of equally long rows.
+    ///
+    /// Panics if the rows differ in length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "every row of a grid must have the same length",
+        );
+        Self {
+            rows: rows.len(),
+            cols,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Parses each line of the input as a row, converting every byte with `parse_cell`.
+    ///
+    /// Panics if the lines differ in length.
+    pub fn parse_from_str(raw_input: &str, mut parse_cell: impl FnMut(u8) -> T) -> Self {
+        Self::from_rows(
+            raw_input
+                .lines()
+                .map(|line| line.bytes().map(&mut parse_cell).collect())
+                .collect(),
+        )
+    }
+
+    /// Parses each line of the input as a row, converting every byte with `parse_cell`,
+    /// which returns `None` for a byte that is not `expected`.
+    pub fn try_parse_from_str(
+        raw_input: &str,
+        expected: &str,
+        mut parse_cell: impl FnMut(u8) -> Option<T>,
+    ) -> Result<Self, ParseError> {
+        let cols = raw_input.lines().next().map_or(0, str::len);
+        let mut cells = Vec::with_capacity(raw_input.len());
+        let mut rows = 0;
+        for line in raw_input.lines() {
+            if line.len() != cols {
+                let expected = format!("a row of {} cells", cols);
+                return Err(ParseError::new(raw_input, line, expected));
+            }
+            for (i, byte) in line.bytes().enumerate() {
+                match parse_cell(byte) {
+                    Some(cell) => cells.push(cell),
+                    None => return Err(ParseError::new(raw_input, &line[i..], expected)),
+                }
+            }
+            rows += 1;
+        }
+        Ok(Self { rows, cols, cells })
+    }
+
+    /// The number of rows in the grid.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The number of columns in the grid.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The `(rows, cols)` size of the grid.
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    fn index_of(&self, (row, col): (usize, usize)) -> Option<usize> {
+        (row < self.rows && col < self.cols).then(|| row * self.cols + col)
+    }
+
+    /// Returns the cell at the given point, or `None` if it is out of bounds.
+    pub fn get(&self, point: (usize, usize)) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    /// Returns the cell at the given point mutably, or `None` if it is out of bounds.
+    pub fn get_mut(&mut self, point: (usize, usize)) -> Option<&mut T> {
+        self.index_of(point).map(move |index| &mut self.cells[index])
+    }
+
+    /// Returns an iterator over every point in the grid, in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = (usize, usize)> {
+        points(self.size())
+    }
+
+    /// Returns an iterator over every cell in the grid, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Returns the cells of the given row.
+    pub fn row(&self, row: usize) -> &[T] {
+        assert!(row < self.rows, "row {} is out of bounds", row);
+        &self.cells[row * self.cols..][..self.cols]
+    }
+
+    /// Returns the cells of the given row mutably.
+    pub fn row_mut(&mut self, row: usize) -> &mut [T] {
+        assert!(row < self.rows, "row {} is out of bounds", row);
+        &mut self.cells[row * self.cols..][..self.cols]
+    }
+
+    /// Returns an iterator over the cells of the given column, from top to bottom.
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.cols, "column {} is out of bounds", col);
+        self.cells.iter().skip(col).step_by(self.cols)
+    }
+
+    /// Returns an iterator over the in-bounds points orthogonally adjacent to `point`.
+    pub fn orthogonal_neighbors(
+        &self,
+        point: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        orthogonal_neighbors(point, self.size())
+    }
+
+    /// Returns an iterator over the in-bounds points surrounding `point`, including diagonals.
+    pub fn surrounding_neighbors(
+        &self,
+        point: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        surrounding_neighbors(point, self.size())
+    }
+
+    /// Returns the point at the given offset from `point`, wrapping around the edges, or
+    /// `None` if the grid is empty.
+    pub fn wrapping_offset(
+        &self,
+        point: (usize, usize),
+        delta: (isize, isize),
+    ) -> Option<(usize, usize)> {
+        wrapping_offset(point, delta, self.size())
+    }
+
+    /// Returns an iterator over the points orthogonally adjacent to `point`, wrapping
+    /// around the edges, which is empty if the grid is.
+    pub fn wrapping_orthogonal_neighbors(
+        &self,
+        point: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let size = self.size();
+        ORTHOGONAL
+            .into_iter()
+            .filter_map(move |delta| wrapping_offset(point, delta, size))
+    }
+
+    /// Returns an iterator over the points surrounding `point`, including diagonals,
+    /// wrapping around the edges, which is empty if the grid is.
+    pub fn wrapping_surrounding_neighbors(
+        &self,
+        point: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let size = self.size();
+        SURROUNDING
+            .into_iter()
+            .filter_map(move |delta| wrapping_offset(point, delta, size))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: (usize, usize)) -> &T {
+        match self.index_of(point) {
+            Some(index) => &self.cells[index],
+            None => panic!("{:?} is out of bounds for a grid of size {:?}", point, self.size()),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, point: (usize, usize)) -> &mut T {
+        match self.index_of(point) {
+            Some(index) => &mut self.cells[index],
+            None => panic!("{:?} is out of bounds for a grid of size {:?}", point, self.size()),
+        }
+    }
+}
+
+/// Displays the grid with one line per row and no separators between cells.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.rows {
+            if row > 0 {
+                writeln!(f)?;
+            }
+            for cell in self.row(row) {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rectangle_is_row_major() {
+        assert_eq!(
+            rectangle((-1, 2), 2, 3).collect::<Vec<_>>(),
+            [(-1, 2), (-1, 3), (-1, 4), (0, 2), (0, 3), (0, 4)],
+        );
+    }
+
+    #[test]
+    fn points_cover_the_grid() {
+        assert_eq!(
+            points((2, 2)).collect::<Vec<_>>(),
+            [(0, 0), (0, 1), (1, 0), (1, 1)],
+        );
+        assert_eq!(points((0, 5)).count(), 0);
+    }
+
+    #[test]
+    fn offsets_stay_in_bounds() {
+        assert_eq!(offset((0, 0), (-1, 0), (3, 3)), None);
+        assert_eq!(offset((0, 0), (0, -1), (3, 3)), None);
+        assert_eq!(offset((2, 2), (1, 0), (3, 3)), None);
+        assert_eq!(offset((2, 2), (0, 1), (3, 3)), None);
+        assert_eq!(offset((1, 1), (1, -1), (3, 3)), Some((2, 0)));
+    }
+
+    #[test]
+    fn corner_neighbors() {
+        assert_eq!(
+            orthogonal_neighbors((0, 0), (3, 3)).collect::<Vec<_>>(),
+            [(0, 1), (1, 0)],
+        );
+        assert_eq!(
+            surrounding_neighbors((0, 0), (3, 3)).collect::<Vec<_>>(),
+            [(0, 1), (1, 1), (1, 0)],
+        );
+    }
+
+    #[test]
+    fn interior_neighbors() {
+        assert_eq!(orthogonal_neighbors((1, 1), (3, 3)).count(), 4);
+        assert_eq!(surrounding_neighbors((1, 1), (3, 3)).count(), 8);
+    }
+
+    #[test]
+    fn wrapping_offsets() {
+        assert_eq!(wrapping_offset((0, 0), (-1, -1), (3, 4)), Some((2, 3)));
+        assert_eq!(wrapping_offset((2, 3), (1, 1), (3, 4)), Some((0, 0)));
+        assert_eq!(wrapping_offset((1, 1), (0, 1), (3, 4)), Some((1, 2)));
+        assert_eq!(wrapping_offset((0, 0), (0, 1), (0, 0)), None);
+        assert_eq!(wrapping_offset((0, 0), (0, 1), (3, 0)), None);
+    }
+
+    fn digits() -> Grid<u8> {
+        Grid::parse_from_str("123\n456", |byte| byte - b'0')
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = digits();
+        assert_eq!(grid.size(), (2, 3));
+        assert_eq!(grid.to_string(), "123\n456");
+        assert_eq!(Grid::parse_from_str("", |byte| byte).size(), (0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn ragged_rows() {
+        Grid::parse_from_str("12\n3", |byte| byte);
+    }
+
+    #[test]
+    fn fallible_parsing() {
+        let parse_digit = |byte: u8| byte.is_ascii_digit().then(|| byte - b'0');
+        assert_eq!(
+            Grid::try_parse_from_str("123\n456", "a digit", parse_digit),
+            Ok(digits()),
+        );
+
+        let error = Grid::try_parse_from_str("123\n4x6", "a digit", parse_digit).unwrap_err();
+        assert_eq!((error.line, error.column, error.expected.as_str()), (2, 2, "a digit"));
+
+        let error = Grid::try_parse_from_str("123\n45", "a digit", parse_digit).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.expected, "a row of 3 cells");
+    }
+
+    #[test]
+    fn indexing() {
+        let mut grid = digits();
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+        grid[(1, 0)] = 0;
+        *grid.get_mut((0, 2)).unwrap() = 9;
+        assert_eq!(grid.to_string(), "129\n056");
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let _ = digits()[(0, 3)];
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let mut grid = digits();
+        assert_eq!(grid.row(1), [4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [3, 6]);
+        grid.row_mut(0).reverse();
+        assert_eq!(grid.to_string(), "321\n456");
+    }
+
+    #[test]
+    fn from_fn_and_iter() {
+        let grid = Grid::from_fn((2, 2), |(row, col)| row * 10 + col);
+        assert_eq!(grid.iter().copied().collect::<Vec<_>>(), [0, 1, 10, 11]);
+        assert_eq!(grid.points().collect::<Vec<_>>(), [(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn grid_neighbors() {
+        let grid = digits();
+        assert_eq!(grid.orthogonal_neighbors((0, 0)).count(), 2);
+        assert_eq!(grid.surrounding_neighbors((0, 1)).count(), 5);
+        assert_eq!(
+            grid.wrapping_orthogonal_neighbors((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1), (1, 0), (0, 2)],
+        );
+        assert_eq!(grid.wrapping_surrounding_neighbors((0, 0)).count(), 8);
+    }
+
+    #[test]
+    fn empty_grids() {
+        let grid = Grid::parse_from_str("", |byte| byte);
+        assert_eq!(grid.size(), (0, 0));
+        assert_eq!(grid.points().count(), 0);
+        assert_eq!(grid.get((0, 0)), None);
+        assert_eq!(grid.wrapping_offset((0, 0), (0, 1)), None);
+        assert_eq!(grid.wrapping_orthogonal_neighbors((0, 0)).count(), 0);
+        assert_eq!(grid.wrapping_surrounding_neighbors((0, 0)).count(), 0);
+        assert_eq!(grid.to_string(), "");
+
+        let grid = Grid::<u8>::from_rows(vec![vec![], vec![]]);
+        assert_eq!(grid.size(), (2, 0));
+        assert!(grid.row(1).is_empty());
+        assert_eq!(grid.wrapping_offset((1, 0), (1, 0)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 2 is out of bounds")]
+    fn row_out_of_bounds_without_columns() {
+        let grid = Grid::<u8>::from_rows(vec![vec![], vec![]]);
+        let _ = grid.row(2);
+    }
+}
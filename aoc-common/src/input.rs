@@ -0,0 +1,428 @@
+//! An offline store of puzzle inputs, laid out as `<root>/<year>/day<NN>.txt`.
+//!
+//! Inputs are gitignored, so nothing but the store itself can tell whether the file in
+//! place is the one that a day's answers were found with. Importing an input records its
+//! SHA-256 in `<root>/SHA256SUMS`, in the format that `sha256sum --check` reads, and
+//! loading it later fails if the file has since changed or holds another day's input.
+
+use crate::parse::{self, ParseError};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Write as _},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The name of the file, directly under the store's root, that records each checksum.
+const MANIFEST: &str = "SHA256SUMS";
+
+/// A puzzle's year and day.
+pub type Puzzle = (u32, u32);
+
+// =====================================================================================
+// Checksums
+// =====================================================================================
+
+/// The SHA-256 of an input file's contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Checksum([u8; 32]);
+
+impl Checksum {
+    pub fn of(contents: &[u8]) -> Self {
+        Self(Sha256::digest(contents).into())
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+impl FromStr for Checksum {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 64 || !s.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(());
+        }
+        let mut bytes = [0; 32];
+        for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap();
+        }
+        Ok(Self(bytes))
+    }
+}
+
+// =====================================================================================
+// Errors
+// =====================================================================================
+
+/// Whether an input file matches the checksum that was recorded when it was imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The file matches its recorded checksum.
+    Verified,
+    /// The file has no recorded checksum, so it cannot be checked.
+    Unrecorded,
+    /// The file has changed since it was imported.
+    Modified,
+    /// The file holds the input that was imported for another puzzle.
+    Swapped(Puzzle),
+}
+
+/// Why an input could not be loaded or imported.
+#[derive(Debug)]
+pub enum InputError {
+    /// A file in the store could not be read or written.
+    Io(PathBuf, io::Error),
+    /// The checksum manifest is malformed.
+    Manifest(PathBuf, ParseError),
+    /// There is no input for the puzzle.
+    Missing(Puzzle, PathBuf),
+    /// There is an input for the puzzle, but at the un-padded path that `cargo aoc input`
+    /// downloads to, rather than in the store.
+    NotImported(Puzzle, PathBuf),
+    /// The input does not match its recorded checksum.
+    Rejected(Puzzle, PathBuf, Status),
+    /// The file being imported is already recorded as the input for another puzzle.
+    Duplicate(Puzzle, Puzzle),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            InputError::Manifest(path, e) => write!(f, "{}: {}", path.display(), e),
+            InputError::Missing((year, day), path) => {
+                write!(
+                    f,
+                    "no input for {} day {}: {} does not exist",
+                    year,
+                    day,
+                    path.display()
+                )
+            }
+            InputError::NotImported((year, day), path) => write!(
+                f,
+                "the input for {} day {} has not been imported; run `aoc input import {} {} {}`",
+                year,
+                day,
+                year,
+                day,
+                path.display(),
+            ),
+            InputError::Rejected((year, day), path, Status::Swapped((other_year, other_day))) => {
+                write!(
+                    f,
+                    "{} holds the input for {} day {}, not {} day {}",
+                    path.display(),
+                    other_year,
+                    other_day,
+                    year,
+                    day,
+                )
+            }
+            InputError::Rejected(_, path, _) => write!(
+                f,
+                "{} has changed since it was imported; import it again if that was intended",
+                path.display(),
+            ),
+            InputError::Duplicate((year, day), (other_year, other_day)) => write!(
+                f,
+                "this is the input for {} day {}, which cannot also be the input for {} day {}",
+                other_year, other_day, year, day,
+            ),
+        }
+    }
+}
+
+impl Error for InputError {}
+
+// =====================================================================================
+// Store
+// =====================================================================================
+
+/// A directory of puzzle inputs along with the checksums that they were imported with.
+#[derive(Debug, Clone)]
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The path that the input for a puzzle is stored at.
+    pub fn path(&self, (year, day): Puzzle) -> PathBuf {
+        self.root
+            .join(year.to_string())
+            .join(format!("day{:02}.txt", day))
+    }
+
+    /// The path that `cargo aoc input` downloads the input for a puzzle to.
+    fn legacy_path(&self, (year, day): Puzzle) -> PathBuf {
+        self.root
+            .join(year.to_string())
+            .join(format!("day{}.txt", day))
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.root.join(MANIFEST)
+    }
+
+    /// The checksum recorded for each imported puzzle.
+    pub fn checksums(&self) -> Result<BTreeMap<Puzzle, Checksum>, InputError> {
+        let path = self.manifest_path();
+        let manifest = match fs::read_to_string(&path) {
+            Ok(manifest) => manifest,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(e) => return Err(InputError::Io(path, e)),
+        };
+        parse_manifest(&manifest).map_err(|e| InputError::Manifest(path, e))
+    }
+
+    /// Reads the input for a puzzle, without checking it.
+    fn read(&self, puzzle: Puzzle) -> Result<String, InputError> {
+        let path = self.path(puzzle);
+        match fs::read_to_string(&path) {
+            Ok(input) => Ok(input),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let legacy_path = self.legacy_path(puzzle);
+                if legacy_path != path && legacy_path.exists() {
+                    Err(InputError::NotImported(puzzle, legacy_path))
+                } else {
+                    Err(InputError::Missing(puzzle, path))
+                }
+            }
+            Err(e) => Err(InputError::Io(path, e)),
+        }
+    }
+
+    /// Checks the input for a puzzle against the recorded checksums.
+    pub fn status(&self, puzzle: Puzzle) -> Result<Status, InputError> {
+        let input = self.read(puzzle)?;
+        Ok(status(&self.checksums()?, puzzle, &input))
+    }
+
+    /// Reads the input for a puzzle, failing unless it matches its recorded checksum or
+    /// has none.
+    pub fn load(&self, puzzle: Puzzle) -> Result<String, InputError> {
+        let input = self.read(puzzle)?;
+        match status(&self.checksums()?, puzzle, &input) {
+            Status::Verified | Status::Unrecorded => Ok(input),
+            status => Err(InputError::Rejected(puzzle, self.path(puzzle), status)),
+        }
+    }
+
+    /// Copies `source` into the store as the input for a puzzle and records its checksum.
+    ///
+    /// Importing a file that `cargo aoc input` downloaded into the store moves it to its
+    /// padded path.
+    pub fn import(&self, puzzle: Puzzle, source: &Path) -> Result<Checksum, InputError> {
+        let input = fs::read(source).map_err(|e| InputError::Io(source.to_path_buf(), e))?;
+        let checksum = Checksum::of(&input);
+        let mut checksums = self.checksums()?;
+        if let Some((&other, _)) = checksums
+            .iter()
+            .find(|&(&other, &recorded)| other != puzzle && recorded == checksum)
+        {
+            return Err(InputError::Duplicate(puzzle, other));
+        }
+
+        let path = self.path(puzzle);
+        let write = |path: &Path, contents: &[u8]| {
+            fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| fs::write(path, contents))
+                .map_err(|e| InputError::Io(path.to_path_buf(), e))
+        };
+        write(&path, &input)?;
+        checksums.insert(puzzle, checksum);
+        write(
+            &self.manifest_path(),
+            render_manifest(&checksums).as_bytes(),
+        )?;
+
+        let legacy_path = self.legacy_path(puzzle);
+        if legacy_path != path && same_file(source, &legacy_path) {
+            fs::remove_file(&legacy_path).map_err(|e| InputError::Io(legacy_path, e))?;
+        }
+        Ok(checksum)
+    }
+}
+
+fn same_file(lhs: &Path, rhs: &Path) -> bool {
+    match (lhs.canonicalize(), rhs.canonicalize()) {
+        (Ok(lhs), Ok(rhs)) => lhs == rhs,
+        _ => false,
+    }
+}
+
+fn status(checksums: &BTreeMap<Puzzle, Checksum>, puzzle: Puzzle, input: &str) -> Status {
+    let checksum = Checksum::of(input.as_bytes());
+    if checksums.get(&puzzle) == Some(&checksum) {
+        return Status::Verified;
+    }
+    match checksums
+        .iter()
+        .find(|&(_, &recorded)| recorded == checksum)
+    {
+        Some((&other, _)) => Status::Swapped(other),
+        None if checksums.contains_key(&puzzle) => Status::Modified,
+        None => Status::Unrecorded,
+    }
+}
+
+// =====================================================================================
+// Manifest
+// =====================================================================================
+
+fn parse_manifest(manifest: &str) -> Result<BTreeMap<Puzzle, Checksum>, ParseError> {
+    manifest
+        .lines()
+        .map(|line| {
+            let [checksum, year, day] = parse::scan(manifest, line, "{}  {}/day{}.txt")?;
+            let checksum = checksum
+                .parse()
+                .map_err(|_| ParseError::new(manifest, checksum, "a SHA-256 checksum"))?;
+            let year = parse::value(manifest, year, "a year")?;
+            let day = parse::value(manifest, day, "a day")?;
+            Ok(((year, day), checksum))
+        })
+        .collect()
+}
+
+fn render_manifest(checksums: &BTreeMap<Puzzle, Checksum>) -> String {
+    let mut manifest = String::new();
+    for ((year, day), checksum) in checksums {
+        writeln!(manifest, "{}  {}/day{:02}.txt", checksum, year, day).unwrap();
+    }
+    manifest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{env, ops::Deref};
+
+    /// A store in a scratch directory, which is removed when the store is dropped.
+    struct ScratchStore(Store);
+
+    impl Deref for ScratchStore {
+        type Target = Store;
+
+        fn deref(&self) -> &Store {
+            &self.0
+        }
+    }
+
+    impl Drop for ScratchStore {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0.root);
+        }
+    }
+
+    /// An empty store in a scratch directory that is unique to the calling test.
+    fn scratch_store(name: &str) -> ScratchStore {
+        let root = env::temp_dir().join(format!("aoc-input-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        ScratchStore(Store::new(root))
+    }
+
+    fn write_source(store: &Store, name: &str, contents: &str) -> PathBuf {
+        let path = store.root.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn checksums() {
+        let checksum = Checksum::of(b"abc");
+        assert_eq!(
+            checksum.to_string(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        );
+        assert_eq!(checksum.to_string().parse(), Ok(checksum));
+        assert_eq!("ba78".parse::<Checksum>(), Err(()));
+    }
+
+    #[test]
+    fn imports_and_loads() {
+        let store = scratch_store("imports_and_loads");
+        let source = write_source(&store, "download.txt", "1\n2\n3\n");
+        let checksum = store.import((2021, 1), &source).unwrap();
+
+        assert!(store.root.join("2021/day01.txt").exists());
+        assert_eq!(store.load((2021, 1)).unwrap(), "1\n2\n3\n");
+        assert_eq!(store.status((2021, 1)).unwrap(), Status::Verified);
+        assert_eq!(
+            fs::read_to_string(store.manifest_path()).unwrap(),
+            format!("{}  2021/day01.txt\n", checksum),
+        );
+    }
+
+    #[test]
+    fn rejects_modified_and_swapped_inputs() {
+        let store = scratch_store("rejects_modified_and_swapped_inputs");
+        let first = write_source(&store, "first.txt", "first");
+        let second = write_source(&store, "second.txt", "second");
+        store.import((2021, 1), &first).unwrap();
+        store.import((2021, 2), &second).unwrap();
+
+        fs::write(store.path((2021, 1)), "first, edited").unwrap();
+        assert_eq!(store.status((2021, 1)).unwrap(), Status::Modified);
+        assert!(matches!(
+            store.load((2021, 1)),
+            Err(InputError::Rejected(_, _, Status::Modified)),
+        ));
+
+        fs::write(store.path((2021, 1)), "second").unwrap();
+        assert_eq!(store.status((2021, 1)).unwrap(), Status::Swapped((2021, 2)));
+        assert!(matches!(
+            store.import((2021, 3), &second),
+            Err(InputError::Duplicate((2021, 3), (2021, 2))),
+        ));
+    }
+
+    #[test]
+    fn reports_missing_and_unimported_inputs() {
+        let store = scratch_store("reports_missing_and_unimported_inputs");
+        assert!(matches!(
+            store.load((2021, 4)),
+            Err(InputError::Missing(..))
+        ));
+
+        fs::create_dir_all(store.root.join("2021")).unwrap();
+        let legacy = write_source(&store, "2021/day4.txt", "4");
+        assert!(matches!(
+            store.load((2021, 4)),
+            Err(InputError::NotImported(..))
+        ));
+
+        store.import((2021, 4), &legacy).unwrap();
+        assert!(!legacy.exists());
+        assert_eq!(store.load((2021, 4)).unwrap(), "4");
+    }
+
+    #[test]
+    fn unrecorded_inputs_still_load() {
+        let store = scratch_store("unrecorded_inputs_still_load");
+        fs::create_dir_all(store.root.join("2024")).unwrap();
+        write_source(&store, "2024/day12.txt", "12");
+        assert_eq!(store.status((2024, 12)).unwrap(), Status::Unrecorded);
+        assert_eq!(store.load((2024, 12)).unwrap(), "12");
+    }
+
+    #[test]
+    fn malformed_manifests() {
+        let error = parse_manifest("abc  2021/day01.txt").unwrap_err();
+        assert_eq!(error.expected, "a SHA-256 checksum");
+        let error = parse_manifest(&format!("{}  2021-day01.txt", Checksum::of(b""))).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 81));
+        assert_eq!(error.expected, "\"/day\"");
+    }
+}
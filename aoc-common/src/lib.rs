@@ -0,0 +1,12 @@
+//! Helpers shared by every year's solutions.
+
+pub mod answers;
+pub mod bench;
+pub mod combinator;
+pub mod examples;
+pub mod grid;
+pub mod input;
+pub mod num;
+pub mod parse;
+pub mod registry;
+pub mod runner;
@@ -0,0 +1,96 @@
+//! Numeric helpers for stepping values and working with digits and bits.
+
+use std::cmp::Ordering;
+
+/// Moves a value one step closer to a target value.
+pub trait Approach {
+    /// Returns `self` moved one unit toward `target`, or `self` if it is already there.
+    fn approach(self, target: Self) -> Self;
+}
+
+macro_rules! impl_approach {
+    ($($int:ty),*) => {
+        $(
+            impl Approach for $int {
+                fn approach(self, target: Self) -> Self {
+                    match target.cmp(&self) {
+                        Ordering::Less => self - 1,
+                        Ordering::Equal => self,
+                        Ordering::Greater => self + 1,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_approach!(i8, i16, i32, i64, i128, isize);
+
+/// Returns the count of digits in a number, counting 0 as a single digit.
+pub fn digit_count(n: i64) -> u32 {
+    n.unsigned_abs().checked_ilog10().map_or(1, |log| log + 1)
+}
+
+/// Returns an iterator over the digits of a number from highest place value to lowest place value.
+pub fn digits(n: i64) -> impl Iterator<Item = i64> {
+    (0..digit_count(n))
+        .rev()
+        .map(move |exp| n / 10_i64.pow(exp) % 10)
+}
+
+/// Returns the concatenation of the digits of two numbers.
+/// e.g. concat_digits(123, 456) == 123456
+pub fn concat_digits(lhs: i64, rhs: i64) -> i64 {
+    lhs * 10_i64.pow(digit_count(rhs)) + rhs
+}
+
+/// Returns the number represented by a slice of bits, where each element is either 0 or 1,
+/// ordered from the highest place value to the lowest place value.
+pub fn from_bits(bits: &[u8]) -> usize {
+    bits.iter()
+        .rev()
+        .zip(0..)
+        .fold(0, |n, (&bit, place_value)| {
+            n | (bit as usize) << place_value
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn approach() {
+        assert_eq!(3_i16.approach(5), 4);
+        assert_eq!(3_i16.approach(3), 3);
+        assert_eq!((-3_i64).approach(-5), -4);
+    }
+
+    #[test]
+    fn digit_counts() {
+        assert_eq!(digit_count(0), 1);
+        assert_eq!(digit_count(7), 1);
+        assert_eq!(digit_count(10), 2);
+        assert_eq!(digit_count(99999999999999), 14);
+    }
+
+    #[test]
+    fn digits_in_order() {
+        assert_eq!(digits(90210).collect::<Vec<_>>(), [9, 0, 2, 1, 0]);
+        assert_eq!(digits(0).collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn concatenation() {
+        assert_eq!(concat_digits(123, 456), 123456);
+        assert_eq!(concat_digits(0, 456), 456);
+        assert_eq!(concat_digits(123, 0), 1230);
+    }
+
+    #[test]
+    fn bits() {
+        assert_eq!(from_bits(&[]), 0);
+        assert_eq!(from_bits(&[1, 0, 1]), 5);
+        assert_eq!(from_bits(&[0, 0, 1, 1, 1, 1, 1, 1, 1]), 127);
+    }
+}
@@ -0,0 +1,215 @@
+//! Helpers for turning raw puzzle input into values.
+
+use std::{error::Error, fmt, str::FromStr};
+
+/// The longest stretch of offending text that an error displays.
+const MAX_FOUND_LEN: usize = 40;
+
+// =====================================================================================
+// Errors
+// =====================================================================================
+
+/// A part of the puzzle input that does not have the shape that a generator expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line that the offending text is on, counting from 1.
+    pub line: usize,
+    /// The column that the offending text starts at, counting characters from 1.
+    pub column: usize,
+    /// The offending text, up to the end of its line.
+    pub found: String,
+    /// What the generator expected to find there, such as `a number`.
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Creates an error for `found`, which must be a slice of `input`.
+    ///
+    /// Panics if `found` does not point into `input`.
+    pub fn new(input: &str, found: &str, expected: impl Into<String>) -> Self {
+        Self::at(input, offset_of(input, found), expected)
+    }
+
+    /// Creates an error for the text at a byte offset into `input`.
+    pub fn at(input: &str, offset: usize, expected: impl Into<String>) -> Self {
+        let offset = (0..=offset.min(input.len()))
+            .rev()
+            .find(|&offset| input.is_char_boundary(offset))
+            .unwrap();
+        let (before, after) = input.split_at(offset);
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            found: after.lines().next().unwrap_or_default().to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Creates an error for input that ended where `expected` should have followed.
+    pub fn end_of_input(input: &str, expected: impl Into<String>) -> Self {
+        Self::at(input, input.len(), expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found ",
+            self.line, self.column, self.expected,
+        )?;
+        if self.found.is_empty() {
+            return write!(f, "the end of the line");
+        }
+        match self.found.char_indices().nth(MAX_FOUND_LEN) {
+            Some((end, _)) => write!(f, "{:?}...", &self.found[..end]),
+            None => write!(f, "{:?}", self.found),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The byte offset of `text`, which must be a slice of `input`, within `input`.
+///
+/// Panics if `text` does not point into `input`.
+pub(crate) fn offset_of(input: &str, text: &str) -> usize {
+    (text.as_ptr() as usize)
+        .checked_sub(input.as_ptr() as usize)
+        .filter(|&offset| offset + text.len() <= input.len())
+        .expect("the offending text must be a slice of the input")
+}
+
+/// Parses `text`, which must be a slice of `input`, reporting what was `expected` if it
+/// does not parse.
+pub fn value<T: FromStr>(input: &str, text: &str, expected: &str) -> Result<T, ParseError> {
+    text.parse().map_err(|_| ParseError::new(input, text, expected))
+}
+
+/// Matches `text`, which must be a slice of `input`, against a pattern in which every
+/// `{}` stands for a non-empty value, and returns the text of each value.
+///
+/// A value runs until the first occurrence of the literal text that follows it in the
+/// pattern, or to the end of `text` if it ends the pattern.
+///
+/// ```
+/// use aoc_common::parse;
+///
+/// let input = "0,9 -> 5,9";
+/// let [x1, y1, x2, y2] = parse::scan(input, input, "{},{} -> {},{}").unwrap();
+/// assert_eq!([x1, y1, x2, y2], ["0", "9", "5", "9"]);
+///
+/// let error = parse::scan::<2>(input, input, "{} => {}").unwrap_err();
+/// assert_eq!(error.to_string(), "line 1, column 11: expected \" => \", found the end of the line");
+/// ```
+///
+/// Panics if the pattern does not have exactly `N` values, or if two of them are adjacent.
+pub fn scan<'a, const N: usize>(
+    input: &str,
+    text: &'a str,
+    pattern: &str,
+) -> Result<[&'a str; N], ParseError> {
+    let literals: Vec<&str> = pattern.split("{}").collect();
+    assert_eq!(literals.len(), N + 1, "pattern must have {} values", N);
+    assert!(
+        literals[1..N].iter().all(|literal| !literal.is_empty()),
+        "values in a pattern must be separated by literal text",
+    );
+
+    let expect_literal = |rest: &'a str, literal: &str| {
+        rest.strip_prefix(literal)
+            .ok_or_else(|| ParseError::new(input, rest, format!("{:?}", literal)))
+    };
+    let mut rest = expect_literal(text, literals[0])?;
+    let mut values = [""; N];
+    for (value, &literal) in values.iter_mut().zip(&literals[1..]) {
+        let end = match literal {
+            "" => rest.len(),
+            literal => rest.find(literal).unwrap_or(rest.len()),
+        };
+        if end == 0 {
+            return Err(ParseError::new(input, rest, "a value"));
+        }
+        *value = &rest[..end];
+        rest = expect_literal(&rest[end..], literal)?;
+    }
+    match rest {
+        "" => Ok(values),
+        rest => Err(ParseError::new(input, rest, "the end of the line")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn error_locations() {
+        let input = "12\n3x4\n\n56";
+        let error = ParseError::new(input, &input[4..5], "a digit");
+        assert_eq!((error.line, error.column), (2, 2));
+        assert_eq!(error.found, "x4");
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 2: expected a digit, found \"x4\"",
+        );
+
+        let error = ParseError::at(input, 7, "a number");
+        assert_eq!((error.line, error.column, error.found.as_str()), (3, 1, ""));
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 1: expected a number, found the end of the line",
+        );
+
+        let error = ParseError::end_of_input(input, "more numbers");
+        assert_eq!((error.line, error.column), (4, 3));
+    }
+
+    #[test]
+    fn error_columns_count_characters() {
+        let input = "héllo wörld";
+        let error = ParseError::new(input, &input[7..], "a greeting");
+        assert_eq!(error.column, 7);
+        assert_eq!(error.found, "wörld");
+    }
+
+    #[test]
+    fn long_found_text_is_truncated() {
+        let input = "x".repeat(100);
+        let error = ParseError::new(&input, &input, "a number");
+        assert_eq!(
+            error.to_string(),
+            format!("line 1, column 1: expected a number, found \"{}\"...", "x".repeat(40)),
+        );
+    }
+
+    #[test]
+    fn values() {
+        let input = "12 ab";
+        assert_eq!(value::<u8>(input, &input[..2], "a number"), Ok(12));
+        let error = value::<u8>(input, &input[3..], "a number").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (4, "ab"));
+    }
+
+    #[test]
+    fn scans() {
+        let input = "fold along x=5\nfold along y=";
+        let line = input.lines().next().unwrap();
+        assert_eq!(scan(input, line, "fold along {}={}"), Ok(["x", "5"]));
+
+        let line = input.lines().nth(1).unwrap();
+        let error = scan::<2>(input, line, "fold along {}={}").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 14));
+        assert_eq!(error.expected, "a value");
+
+        let error = scan::<2>(input, line, "fold across {}={}").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.expected, "\"fold across \"");
+
+        let line = input.lines().next().unwrap();
+        let error = scan::<1>(input, line, "fold along {}=").unwrap_err();
+        assert_eq!((error.column, error.found.as_str()), (14, "5"));
+        assert_eq!(error.expected, "the end of the line");
+    }
+}
@@ -0,0 +1,207 @@
+//! A build-time table of the solutions that a year crate registers with `#[aoc(...)]`.
+//!
+//! `aoc_main!` finds solutions by reading `target/aoc/completed.json`, which is rewritten
+//! by every `aoc_lib!` invocation. When several year crates are built together in one
+//! workspace, they overwrite each other's copy of that file, so instead each year crate
+//! scans its own sources from a build script and includes the generated table:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     aoc_common::registry::generate().unwrap();
+//! }
+//!
+//! // lib.rs, after `aoc_lib!`
+//! include!(concat!(env!("OUT_DIR"), "/solutions.rs"));
+//! ```
+
+use aoc_runner::{ArcStr, Runner};
+use std::{
+    env,
+    error::Error,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Builds the runner for a solution from its raw puzzle input.
+pub type Factory = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+/// A single `#[aoc(dayN, partN, Variant)]` solution.
+#[derive(Clone, Copy)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    pub variant: Option<&'static str>,
+    pub factory: Factory,
+}
+
+/// The day, part, and optional variant named by an `#[aoc(...)]` attribute.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct DayPart {
+    pub(crate) day: u32,
+    pub(crate) part: u32,
+    pub(crate) variant: Option<String>,
+}
+
+impl DayPart {
+    /// Parses the attribute on a line such as `#[aoc(day16, part1, nordzilla)]`.
+    fn from_attribute(line: &str) -> Option<Self> {
+        let mut args = line
+            .trim()
+            .strip_prefix("#[aoc(")?
+            .strip_suffix(")]")?
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|arg| !arg.is_empty());
+        let day = args.next()?.strip_prefix("day")?.parse().ok()?;
+        let part = args.next()?.strip_prefix("part")?.parse().ok()?;
+        let variant = args.next().map(str::to_string);
+        Some(Self { day, part, variant })
+    }
+
+    /// The path to the factory function that `aoc_lib!` generates for this solution.
+    fn factory_path(&self) -> String {
+        let Self { day, part, variant } = self;
+        match variant {
+            Some(variant) => format!(
+                "<crate::Factory as crate::Day{day}Part{part}{}>::day{day}_part{part}_{}",
+                variant.to_uppercase(),
+                variant.to_lowercase(),
+            ),
+            None => format!(
+                "<crate::Factory as crate::Day{day}Part{part}>::day{day}_part{part}",
+            ),
+        }
+    }
+
+    /// The source of the `Solution` for this solution.
+    pub(crate) fn literal(&self) -> String {
+        format!(
+            "aoc_common::registry::Solution {{ day: {}, part: {}, variant: {:?}, factory: {} }}",
+            self.day,
+            self.part,
+            self.variant,
+            self.factory_path(),
+        )
+    }
+}
+
+/// Returns the name of the module declared on a line such as `pub mod day01;`.
+fn module_declaration(line: &str) -> Option<&str> {
+    let line = line.trim();
+    let line = line
+        .strip_prefix("pub(crate) ")
+        .or_else(|| line.strip_prefix("pub "))
+        .unwrap_or(line);
+    line.strip_prefix("mod ")?.strip_suffix(';')
+}
+
+/// Collects the solutions registered in a module file and, recursively, in the files of
+/// the modules that it declares, which live in `children_dir`.
+fn scan_module(
+    file: &Path,
+    children_dir: &Path,
+    day_parts: &mut Vec<DayPart>,
+) -> Result<(), Box<dyn Error>> {
+    for line in fs::read_to_string(file)?.lines() {
+        if let Some(day_part) = DayPart::from_attribute(line) {
+            day_parts.push(day_part);
+        } else if let Some(name) = module_declaration(line) {
+            let child = children_dir.join(format!("{}.rs", name));
+            if child.exists() {
+                scan_module(&child, &children_dir.join(name), day_parts)?;
+            } else {
+                let dir = children_dir.join(name);
+                scan_module(&dir.join("mod.rs"), &dir, day_parts)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns every solution registered in the modules of the library rooted at `src_dir/lib.rs`.
+pub(crate) fn scan(src_dir: &Path) -> Result<Vec<DayPart>, Box<dyn Error>> {
+    let mut day_parts = Vec::new();
+    scan_module(&src_dir.join("lib.rs"), src_dir, &mut day_parts)?;
+    day_parts.sort();
+    day_parts.dedup();
+    Ok(day_parts)
+}
+
+/// Renders the source of the `SOLUTIONS` table for the given solutions.
+fn render(day_parts: &[DayPart]) -> String {
+    let mut source = String::from("pub const SOLUTIONS: &[aoc_common::registry::Solution] = &[\n");
+    for day_part in day_parts {
+        writeln!(source, "    {},", day_part.literal()).unwrap();
+    }
+    source.push_str("];\n");
+    source
+}
+
+/// Scans the calling crate's library modules and writes `solutions.rs` into `OUT_DIR`.
+///
+/// Intended to be called from a year crate's build script.
+pub fn generate() -> Result<(), Box<dyn Error>> {
+    let src_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?).join("src");
+    let out_file = PathBuf::from(env::var("OUT_DIR")?).join("solutions.rs");
+    fs::write(out_file, render(&scan(&src_dir)?))?;
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    pub(crate) fn day_part(day: u32, part: u32, variant: Option<&str>) -> DayPart {
+        DayPart {
+            day,
+            part,
+            variant: variant.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parses_attributes() {
+        assert_eq!(
+            DayPart::from_attribute("#[aoc(day5, part2)]"),
+            Some(day_part(5, 2, None)),
+        );
+        assert_eq!(
+            DayPart::from_attribute("    #[aoc(day16, part1, nordzilla)]"),
+            Some(day_part(16, 1, Some("nordzilla"))),
+        );
+        assert_eq!(
+            DayPart::from_attribute("#[aoc(day23 part2, nordzilla)]"),
+            Some(day_part(23, 2, Some("nordzilla"))),
+        );
+    }
+
+    #[test]
+    fn ignores_other_lines() {
+        assert_eq!(DayPart::from_attribute("#[aoc_generator(day5)]"), None);
+        assert_eq!(DayPart::from_attribute("// #[aoc(day5, part1)]"), None);
+        assert_eq!(DayPart::from_attribute("#[aoc(day5)]"), None);
+    }
+
+    #[test]
+    fn parses_module_declarations() {
+        assert_eq!(module_declaration("pub mod day01;"), Some("day01"));
+        assert_eq!(module_declaration("mod encoder;"), Some("encoder"));
+        assert_eq!(module_declaration("pub(crate) mod grid;"), Some("grid"));
+        assert_eq!(module_declaration("//pub mod day03;"), None);
+        assert_eq!(module_declaration("mod test {"), None);
+    }
+
+    #[test]
+    fn factory_paths_match_aoc_lib() {
+        assert_eq!(
+            day_part(1, 2, Some("Clever")).factory_path(),
+            "<crate::Factory as crate::Day1Part2CLEVER>::day1_part2_clever",
+        );
+        assert_eq!(
+            day_part(3, 1, None).factory_path(),
+            "<crate::Factory as crate::Day3Part1>::day3_part1",
+        );
+    }
+}
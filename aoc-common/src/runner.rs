@@ -0,0 +1,575 @@
+//! Runs a year's registered solutions against its puzzle inputs, in place of `aoc_main!`.
+
+use crate::{
+    answers::Answers,
+    input::{Checksum, InputError, Store},
+    registry::Solution,
+};
+use aoc_runner::ArcStr;
+use serde::Serialize;
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// Runs every solution and prints its answer along with the time spent in the generator
+/// and in the solver, in the same format as `aoc_main!`.
+///
+/// Inputs are loaded at runtime from the store, so a missing, modified, or swapped input
+/// fails only the days that need it. Likewise, a generator that rejects its input fails
+/// only its own day, with the error that it reported.
+pub fn run_all(year: u32, solutions: &[Solution], inputs: &Store) {
+    println!("Advent of code {}", year);
+    for solution in solutions {
+        let label = solution.label();
+
+        let input = match inputs.load((year, solution.day)) {
+            Ok(input) => ArcStr::from(&input),
+            Err(e) => {
+                eprintln!("{}: FAILED while loading input:\n{}\n", label, e);
+                continue;
+            }
+        };
+
+        let start_time = Instant::now();
+        match (solution.factory)(input) {
+            Ok(runner) => {
+                let inter_time = Instant::now();
+                match runner.try_run() {
+                    Ok(result) => {
+                        let final_time = Instant::now();
+                        println!(
+                            "{}: {}\n\tgenerator: {:?},\n\trunner: {:?}\n",
+                            label,
+                            result,
+                            inter_time - start_time,
+                            final_time - inter_time,
+                        );
+                    }
+                    Err(e) => eprintln!("{}: FAILED while running:\n{:#?}\n", label, e),
+                }
+            }
+            Err(e) => eprintln!("{}: FAILED while generating:\n{}\n", label, e),
+        }
+    }
+}
+
+impl Solution {
+    /// The name that `aoc_main!` prints before a solution's answer.
+    fn label(&self) -> String {
+        match self.variant {
+            Some(variant) => format!("Day {} - Part {} - {}", self.day, self.part, variant),
+            None => format!("Day {} - Part {}", self.day, self.part),
+        }
+    }
+}
+
+/// The outcome of checking one solution against its recorded answer.
+enum Verdict {
+    /// The solution gave the recorded answer.
+    Pass(Duration),
+    /// The solution gave some other answer.
+    Fail(String, Duration),
+    /// The solution could not be run, or reported an error, or panicked.
+    Error(String),
+    /// There is nothing to check the solution against, or its answers say not to check it.
+    Skip(String),
+}
+
+/// Runs every solution that has a recorded answer and prints a table of which ones gave
+/// it, along with the time that each took. Returns whether none of them failed and at least
+/// one of them passed, so that a year with no answers or no inputs does not pass unchecked.
+///
+/// A solution is skipped, rather than failed, when its day records no answer for its part
+/// or its variant, or a reason to ignore it, when there is no input for it, or when its
+/// day's answers were found with another input.
+pub fn verify_all(year: u32, solutions: &[Solution], inputs: &Store, answers: &Answers) -> bool {
+    println!("Verifying advent of code {}", year);
+    let labels: Vec<_> = solutions.iter().map(Solution::label).collect();
+    let width = labels.iter().map(String::len).max().unwrap_or(0);
+
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for (solution, label) in solutions.iter().zip(&labels) {
+        let expected = answers.expected(solution.day, solution.part, solution.variant);
+        let (status, details) = match verify(year, solution, inputs, answers) {
+            Verdict::Pass(time) => {
+                passed += 1;
+                (
+                    "pass",
+                    format!("{:<20} {:?}", expected.unwrap_or_default(), time),
+                )
+            }
+            Verdict::Fail(found, time) => {
+                failed += 1;
+                let mismatch = format!("{} (expected {})", found, expected.unwrap_or_default());
+                ("FAIL", format!("{:<20} {:?}", mismatch, time))
+            }
+            Verdict::Error(e) => {
+                failed += 1;
+                ("FAIL", e)
+            }
+            Verdict::Skip(reason) => {
+                skipped += 1;
+                ("skip", reason)
+            }
+        };
+        println!("{:<width$}  {}  {}", label, status, details, width = width);
+    }
+    println!(
+        "\n{} passed, {} failed, {} skipped",
+        passed, failed, skipped
+    );
+    if passed == 0 {
+        eprintln!(
+            "warning: no solution for {} was verified, as every one was skipped",
+            year
+        );
+    }
+    failed == 0 && passed > 0
+}
+
+fn verify(year: u32, solution: &Solution, inputs: &Store, answers: &Answers) -> Verdict {
+    if let Some(reason) = answers.ignored(solution.day, solution.part) {
+        return Verdict::Skip(reason.to_string());
+    }
+    let Some(expected) = answers.expected(solution.day, solution.part, solution.variant) else {
+        return Verdict::Skip("no recorded answer".to_string());
+    };
+    let input = match inputs.load((year, solution.day)) {
+        Ok(input) => input,
+        Err(InputError::Missing(..)) => return Verdict::Skip("no input".to_string()),
+        Err(e) => return Verdict::Error(e.to_string()),
+    };
+    if let Some(checksum) = answers.input(solution.day) {
+        if checksum != Checksum::of(input.as_bytes()) {
+            return Verdict::Skip("the recorded answers are for another input".to_string());
+        }
+    }
+
+    match attempt(solution, ArcStr::from(&input)) {
+        Ok(run) if run.answer == expected => Verdict::Pass(run.generator + run.runner),
+        Ok(run) => Verdict::Fail(run.answer, run.generator + run.runner),
+        Err(e) => Verdict::Error(e),
+    }
+}
+
+/// The answer that a solution gave and the time spent in its generator and its solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub answer: String,
+    pub generator: Duration,
+    pub runner: Duration,
+}
+
+/// Runs a solution once, turning a generator or solver that fails or panics into an error.
+fn attempt(solution: &Solution, input: ArcStr) -> Result<Run, String> {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let start_time = Instant::now();
+        let runner =
+            (solution.factory)(input).map_err(|e| format!("failed while generating: {}", e))?;
+        let inter_time = Instant::now();
+        let answer = runner
+            .try_run()
+            .map_err(|e| format!("failed while running: {}", e))?
+            .to_string();
+        let final_time = Instant::now();
+        Ok(Run {
+            answer,
+            generator: inter_time - start_time,
+            runner: final_time - inter_time,
+        })
+    }));
+    result.unwrap_or_else(|_| Err("panicked".to_string()))
+}
+
+/// The spread of the times taken over several runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Timings {
+    min: Duration,
+    median: Duration,
+    max: Duration,
+}
+
+impl Timings {
+    fn of(mut times: Vec<Duration>) -> Self {
+        times.sort();
+        Self {
+            min: times[0],
+            median: times[times.len() / 2],
+            max: times[times.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10.1?} {:>10.1?} {:>10.1?}",
+            self.min, self.median, self.max
+        )
+    }
+}
+
+/// Runs every variant of a day's solutions `runs` times against the same input and prints
+/// the answer that each gave, along with the minimum, median, and maximum time spent in its
+/// generator and in its solver. Returns whether every variant of each part gave the same
+/// answer without failing.
+pub fn compare_day(
+    year: u32,
+    day: u32,
+    solutions: &[Solution],
+    inputs: &Store,
+    runs: usize,
+) -> bool {
+    let solutions: Vec<_> = solutions.iter().filter(|s| s.day == day).collect();
+    if solutions.is_empty() {
+        eprintln!("There are no solutions for {} day {}", year, day);
+        return false;
+    }
+    let input = match inputs.load((year, day)) {
+        Ok(input) => ArcStr::from(&input),
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+
+    println!(
+        "Comparing advent of code {} day {} over {} runs",
+        year,
+        day,
+        runs.max(1)
+    );
+    let variants: Vec<_> = solutions
+        .iter()
+        .map(|s| s.variant.unwrap_or("(default)"))
+        .collect();
+    let width = variants.iter().map(|v| v.len()).max().unwrap_or(0).max(6);
+
+    let mut agreed = true;
+    for part in [1, 2] {
+        if !solutions.iter().any(|s| s.part == part) {
+            continue;
+        }
+        println!(
+            "\n{:<width$}  {:<20}  {:>32}  {:>32}",
+            format!("Part {}", part),
+            "answer",
+            "generator (min, median, max)",
+            "solver (min, median, max)",
+            width = width,
+        );
+        let mut answers = Vec::new();
+        for (solution, variant) in solutions.iter().zip(&variants) {
+            if solution.part != part {
+                continue;
+            }
+            let runs: Result<Vec<_>, _> = (0..runs.max(1))
+                .map(|_| attempt(solution, input.clone()))
+                .collect();
+            match runs {
+                Ok(runs) => {
+                    println!(
+                        "{:<width$}  {:<20}  {}  {}",
+                        variant,
+                        runs[0].answer,
+                        Timings::of(runs.iter().map(|run| run.generator).collect()),
+                        Timings::of(runs.iter().map(|run| run.runner).collect()),
+                        width = width,
+                    );
+                    answers.push(runs[0].answer.clone());
+                }
+                Err(e) => {
+                    println!("{:<width$}  FAILED {}", variant, e, width = width);
+                    agreed = false;
+                }
+            }
+        }
+        answers.dedup();
+        if answers.len() > 1 {
+            println!("The variants of part {} disagree", part);
+            agreed = false;
+        }
+    }
+    agreed
+}
+
+/// The outcome of running one of a year's solutions on its stored input.
+pub struct Outcome {
+    pub year: u32,
+    pub solution: Solution,
+    /// The answer and timings, or why the solution gave no answer.
+    pub result: Result<Run, String>,
+}
+
+/// Runs a solution once on its stored input.
+pub fn run(year: u32, solution: &Solution, inputs: &Store) -> Outcome {
+    let result = match inputs.load((year, solution.day)) {
+        Ok(input) => attempt(solution, ArcStr::from(&input)),
+        Err(e) => Err(e.to_string()),
+    };
+    Outcome {
+        year,
+        solution: *solution,
+        result,
+    }
+}
+
+/// Renders a table of outcomes, with a row for each solution that gives its answer and the
+/// time spent in its generator and in its solver, or else why it failed.
+pub fn table(outcomes: &[Outcome]) -> String {
+    let header = [
+        "Year",
+        "Day",
+        "Part",
+        "Variant",
+        "Answer",
+        "Generator",
+        "Solver",
+    ];
+    let rows: Vec<[String; 7]> = outcomes
+        .iter()
+        .map(|outcome| {
+            let Outcome {
+                year,
+                solution,
+                result,
+            } = outcome;
+            let (answer, generator, runner) = match result {
+                Ok(run) => (
+                    run.answer.clone(),
+                    format!("{:.1?}", run.generator),
+                    format!("{:.1?}", run.runner),
+                ),
+                Err(e) => (format!("FAILED: {}", e), String::new(), String::new()),
+            };
+            [
+                year.to_string(),
+                solution.day.to_string(),
+                solution.part.to_string(),
+                solution.variant.unwrap_or("(default)").to_string(),
+                answer,
+                generator,
+                runner,
+            ]
+        })
+        .collect();
+
+    // A failure's reason is the last thing on its row, so it does not widen the answers.
+    let mut widths = header.map(str::len);
+    for (row, outcome) in rows.iter().zip(outcomes) {
+        for (i, (width, cell)) in widths.iter_mut().zip(row).enumerate() {
+            if i != 4 || outcome.result.is_ok() {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+    }
+    let mut table = String::new();
+    for row in std::iter::once(header.map(str::to_string)).chain(rows) {
+        let mut line = String::new();
+        for (i, (cell, &width)) in row.iter().zip(&widths).enumerate() {
+            let separator = if i == 0 { "" } else { "  " };
+            match i {
+                // The day, the part, and the times line up on the right.
+                1 | 2 | 5 | 6 => line += &format!("{}{:>width$}", separator, cell, width = width),
+                _ => line += &format!("{}{:<width$}", separator, cell, width = width),
+            }
+        }
+        table += line.trim_end();
+        table.push('\n');
+    }
+    table
+}
+
+/// How `aoc run` prints outcomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// An aligned table for reading, from [`table`].
+    Table,
+    /// A JSON array of [`Record`]s.
+    Json,
+    /// A CSV file of [`Record`]s, with a header row.
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An outcome in the form that the JSON and CSV formats emit, with times in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Record<'a> {
+    pub year: u32,
+    pub day: u32,
+    pub part: u32,
+    pub variant: Option<&'a str>,
+    pub answer: Option<&'a str>,
+    pub generator_ns: Option<u128>,
+    pub solver_ns: Option<u128>,
+    pub error: Option<&'a str>,
+}
+
+impl<'a> From<&'a Outcome> for Record<'a> {
+    fn from(outcome: &'a Outcome) -> Self {
+        let run = outcome.result.as_ref().ok();
+        Record {
+            year: outcome.year,
+            day: outcome.solution.day,
+            part: outcome.solution.part,
+            variant: outcome.solution.variant,
+            answer: run.map(|run| run.answer.as_str()),
+            generator_ns: run.map(|run| run.generator.as_nanos()),
+            solver_ns: run.map(|run| run.runner.as_nanos()),
+            error: outcome.result.as_ref().err().map(String::as_str),
+        }
+    }
+}
+
+/// Quotes a CSV field if it holds a comma, a quote, or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Renders outcomes in the given format.
+pub fn render(outcomes: &[Outcome], format: Format) -> String {
+    let records = outcomes.iter().map(Record::from);
+    match format {
+        Format::Table => table(outcomes),
+        Format::Json => serde_json::to_string_pretty(&records.collect::<Vec<_>>()).unwrap() + "\n",
+        Format::Csv => {
+            let mut csv = "year,day,part,variant,answer,generator_ns,solver_ns,error\n".to_string();
+            for record in records {
+                let number = |n: Option<u128>| n.map(|n| n.to_string()).unwrap_or_default();
+                let text = |s: Option<&str>| csv_field(s.unwrap_or_default());
+                csv += &format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    record.year,
+                    record.day,
+                    record.part,
+                    text(record.variant),
+                    text(record.answer),
+                    number(record.generator_ns),
+                    number(record.solver_ns),
+                    text(record.error),
+                );
+            }
+            csv
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solution(day: u32, part: u32, variant: Option<&'static str>) -> Solution {
+        Solution {
+            day,
+            part,
+            variant,
+            factory: |_| Err("unused".into()),
+        }
+    }
+
+    fn outcomes() -> [Outcome; 2] {
+        [
+            Outcome {
+                year: 2021,
+                solution: solution(1, 2, Some("Clever")),
+                result: Ok(Run {
+                    answer: "1127".to_string(),
+                    generator: Duration::from_micros(12),
+                    runner: Duration::from_nanos(800),
+                }),
+            },
+            Outcome {
+                year: 2021,
+                solution: solution(16, 1, None),
+                result: Err("failed while running: panicked".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn renders_tables() {
+        assert_eq!(
+            table(&outcomes()),
+            "Year  Day  Part  Variant    Answer  Generator   Solver\n\
+             2021    1     2  Clever     1127       12.0µs  800.0ns\n\
+             2021   16     1  (default)  FAILED: failed while running: panicked\n",
+        );
+    }
+
+    #[test]
+    fn renders_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&outcomes(), Format::Json)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "year": 2021, "day": 1, "part": 2, "variant": "Clever", "answer": "1127",
+                    "generator_ns": 12000, "solver_ns": 800, "error": null,
+                },
+                {
+                    "year": 2021, "day": 16, "part": 1, "variant": null, "answer": null,
+                    "generator_ns": null, "solver_ns": null,
+                    "error": "failed while running: panicked",
+                },
+            ]),
+        );
+    }
+
+    #[test]
+    fn renders_csv() {
+        assert_eq!(
+            render(&outcomes(), Format::Csv),
+            "year,day,part,variant,answer,generator_ns,solver_ns,error\n\
+             2021,1,2,Clever,1127,12000,800,\n\
+             2021,16,1,,,,,failed while running: panicked\n",
+        );
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("#..#\n#..#"), "\"#..#\n#..#\"");
+    }
+
+    #[test]
+    fn verifying_nothing_fails() {
+        let solutions = [solution(1, 1, None), solution(1, 2, None)];
+        let inputs = Store::new(env!("CARGO_MANIFEST_DIR"));
+        assert!(!verify_all(2021, &solutions, &inputs, &Answers::default()));
+        assert!(!verify_all(2021, &[], &inputs, &Answers::default()));
+    }
+
+    #[test]
+    fn timings() {
+        let times = [5, 1, 4, 2, 3].map(Duration::from_millis).to_vec();
+        assert_eq!(
+            Timings::of(times),
+            Timings {
+                min: Duration::from_millis(1),
+                median: Duration::from_millis(3),
+                max: Duration::from_millis(5),
+            }
+        );
+        assert_eq!(
+            Timings::of(vec![Duration::from_micros(1500)]).to_string(),
+            "     1.5ms      1.5ms      1.5ms",
+        );
+    }
+}
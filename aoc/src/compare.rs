@@ -0,0 +1,25 @@
+//! The `aoc compare` subcommand.
+
+use crate::{day, year, Failure};
+use aoc_common::runner;
+
+/// How many times each variant runs when `--runs` is not given.
+const DEFAULT_RUNS: usize = 10;
+
+pub fn run(args: &[&str]) -> Result<(), Failure> {
+    let (year_arg, day_arg, runs) = match args {
+        [year, day] => (year, day, DEFAULT_RUNS),
+        [year, day, "--runs", runs] => match runs.parse() {
+            Ok(runs @ 1..) => (year, day, runs),
+            _ => return Err(Failure::Usage),
+        },
+        _ => return Err(Failure::Usage),
+    };
+    let day = day(day_arg)?;
+    let year = year(year_arg)?;
+    if runner::compare_day(year.year, day, year.solutions, &(year.inputs)(), runs) {
+        Ok(())
+    } else {
+        Err(Failure::Failed)
+    }
+}
@@ -0,0 +1,74 @@
+//! The `aoc input` subcommand.
+
+use crate::{day, store, Failure, YEARS};
+use aoc_common::input::{InputError, Status, Store};
+use std::path::Path;
+
+pub fn run(args: &[&str]) -> Result<(), Failure> {
+    match args {
+        ["import", year, day_arg, file] => import(year, day(day_arg)?, Path::new(file)),
+        ["status"] => {
+            let results: Vec<_> = YEARS
+                .iter()
+                .map(|year| status(year.year, &(year.inputs)()))
+                .collect();
+            results.into_iter().collect()
+        }
+        ["status", year] => {
+            let (year, inputs) = store(year)?;
+            status(year, &inputs)
+        }
+        _ => Err(Failure::Usage),
+    }
+}
+
+fn import(year: &str, day: u32, file: &Path) -> Result<(), Failure> {
+    let (year, inputs) = store(year)?;
+    match inputs.import((year, day), file) {
+        Ok(checksum) => {
+            println!("{}  {}", checksum, inputs.path((year, day)).display());
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("aoc: {}", e);
+            Err(Failure::Failed)
+        }
+    }
+}
+
+/// Reports on every input of a year that is stored, recorded, or waiting to be imported,
+/// failing if any of them would not load.
+fn status(year: u32, inputs: &Store) -> Result<(), Failure> {
+    let checksums = inputs.checksums().map_err(|e| {
+        eprintln!("aoc: {}", e);
+        Failure::Failed
+    })?;
+    let mut healthy = true;
+    for day in 1..=25 {
+        let (ok, report) = match inputs.status((year, day)) {
+            Ok(Status::Verified) => (true, "verified".to_string()),
+            Ok(Status::Unrecorded) => (true, "no recorded checksum".to_string()),
+            Ok(Status::Modified) => (false, "modified since it was imported".to_string()),
+            Ok(Status::Swapped((other_year, other_day))) => (
+                false,
+                format!("holds the input for {} day {}", other_year, other_day),
+            ),
+            Err(InputError::Missing(..)) if !checksums.contains_key(&(year, day)) => continue,
+            Err(InputError::Missing(..)) => (false, "missing".to_string()),
+            Err(InputError::NotImported(_, path)) => {
+                (false, format!("not imported from {}", path.display()))
+            }
+            Err(e) => {
+                eprintln!("aoc: {}", e);
+                return Err(Failure::Failed);
+            }
+        };
+        healthy &= ok;
+        println!("{} day {:02}: {}", year, day, report);
+    }
+    if healthy {
+        Ok(())
+    } else {
+        Err(Failure::Failed)
+    }
+}
@@ -0,0 +1,34 @@
+//! The `aoc list` subcommand.
+
+use crate::{year, Failure, Year, YEARS};
+
+pub fn run(args: &[&str]) -> Result<(), Failure> {
+    let years = match args {
+        [] => YEARS.iter().collect(),
+        [year_arg] => vec![year(year_arg)?],
+        _ => return Err(Failure::Usage),
+    };
+    println!("Year  Day  Part  Variants");
+    for year in years {
+        list(year);
+    }
+    Ok(())
+}
+
+/// Prints a line for each part of each day that a year solves, naming its variants.
+fn list(year: &Year) {
+    let mut solutions = year.solutions.iter().peekable();
+    while let Some(first) = solutions.next() {
+        let mut variants = vec![first.variant.unwrap_or("(default)")];
+        while let Some(next) = solutions.next_if(|s| (s.day, s.part) == (first.day, first.part)) {
+            variants.push(next.variant.unwrap_or("(default)"));
+        }
+        println!(
+            "{:<4}  {:>3}  {:>4}  {}",
+            year.year,
+            first.day,
+            first.part,
+            variants.join(", ")
+        );
+    }
+}
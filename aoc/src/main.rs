@@ -0,0 +1,117 @@
+//! Runs every year's solutions and manages the workspace's puzzle inputs.
+//!
+//! ```text
+//! aoc run YEAR [DAY] [--part PART] [--variant VARIANT] [--format table|json|csv]
+//!                                  run solutions and print their answers
+//! aoc list [YEAR]                  list the days, parts, and variants that are solved
+//! aoc input import YEAR DAY FILE   copy FILE into the store and record its checksum
+//! aoc input status [YEAR]          check every stored input against its checksum
+//! aoc verify [YEAR]                check every solution against its recorded answer
+//! aoc compare YEAR DAY [--runs N]  time every variant of a day and check that they agree
+//! aoc new YEAR DAY                 add a day to a year crate from its template
+//! ```
+
+use aoc_common::{
+    answers::{Answers, AnswersError},
+    input::Store,
+    registry::Solution,
+};
+use std::{env, process};
+
+mod compare;
+mod input;
+mod list;
+mod new;
+mod run;
+mod verify;
+
+/// A year crate in the workspace.
+struct Year {
+    year: u32,
+    inputs: fn() -> Store,
+    answers: fn() -> Result<Answers, AnswersError>,
+    solutions: &'static [Solution],
+    manifest_dir: &'static str,
+}
+
+const YEARS: &[Year] = &[
+    Year {
+        year: 2021,
+        inputs: aoc_rust_2021::inputs,
+        answers: aoc_rust_2021::answers,
+        solutions: aoc_rust_2021::SOLUTIONS,
+        manifest_dir: aoc_rust_2021::MANIFEST_DIR,
+    },
+    Year {
+        year: 2024,
+        inputs: aoc_rust_2024::inputs,
+        answers: aoc_rust_2024::answers,
+        solutions: aoc_rust_2024::SOLUTIONS,
+        manifest_dir: aoc_rust_2024::MANIFEST_DIR,
+    },
+];
+
+const USAGE: &str =
+    "usage: aoc run YEAR [DAY] [--part PART] [--variant VARIANT] [--format table|json|csv]
+       aoc list [YEAR]
+       aoc input import YEAR DAY FILE
+       aoc input status [YEAR]
+       aoc verify [YEAR]
+       aoc compare YEAR DAY [--runs N]
+       aoc new YEAR DAY";
+
+/// The reason that a command did not succeed.
+enum Failure {
+    /// The command line was malformed.
+    Usage,
+    /// The command ran but failed, and has already reported why.
+    Failed,
+}
+
+/// Looks up the crate for a year.
+fn year(year: &str) -> Result<&'static Year, Failure> {
+    let year: u32 = year.parse().map_err(|_| Failure::Usage)?;
+    match YEARS.iter().find(|known| known.year == year) {
+        Some(known) => Ok(known),
+        None => {
+            eprintln!("aoc: there is no crate for {}", year);
+            Err(Failure::Failed)
+        }
+    }
+}
+
+/// Looks up the input store of the crate for a year.
+fn store(year: &str) -> Result<(u32, Store), Failure> {
+    let year = self::year(year)?;
+    Ok((year.year, (year.inputs)()))
+}
+
+/// Parses a day of the month from 1 to 25.
+fn day(day: &str) -> Result<u32, Failure> {
+    match day.parse() {
+        Ok(day @ 1..=25) => Ok(day),
+        _ => Err(Failure::Usage),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["run", args @ ..] => run::run(args),
+        ["list", args @ ..] => list::run(args),
+        ["input", args @ ..] => input::run(args),
+        ["verify", args @ ..] => verify::run(args),
+        ["compare", args @ ..] => compare::run(args),
+        ["new", args @ ..] => new::run(args),
+        _ => Err(Failure::Usage),
+    };
+    match result {
+        Ok(()) => {}
+        Err(Failure::Usage) => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+        Err(Failure::Failed) => process::exit(1),
+    }
+}
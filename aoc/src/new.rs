@@ -0,0 +1,204 @@
+//! The `aoc new` subcommand.
+
+use crate::{day, year, Failure, Year};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+pub fn run(args: &[&str]) -> Result<(), Failure> {
+    match args {
+        [year_arg, day_arg] => new(year(year_arg)?, day(day_arg)?),
+        _ => Err(Failure::Usage),
+    }
+}
+
+/// Replaces the day in every `#[aoc(...)]` and `#[aoc_generator(...)]` attribute of the
+/// template with the given day.
+fn instantiate(template: &str, day: u32) -> String {
+    let mut source = String::new();
+    for line in template.split_inclusive('\n') {
+        let attribute = line.trim_start().starts_with("#[aoc");
+        match line.split_once("(day") {
+            Some((head, tail)) if attribute => {
+                let tail = tail.trim_start_matches(|c: char| c.is_ascii_digit());
+                source += &format!("{}(day{}{}", head, day, tail);
+            }
+            _ => source += line,
+        }
+    }
+    source
+}
+
+/// Returns the module name of a line such as `pub mod day05;` or `//pub mod day05;`, along
+/// with whether the line is commented out.
+fn day_module(line: &str) -> Option<(&str, bool)> {
+    let line = line.trim();
+    let (line, commented) = match line.strip_prefix("//") {
+        Some(line) => (line.trim_start(), true),
+        None => (line, false),
+    };
+    let module = line.strip_prefix("pub mod ")?.strip_suffix(';')?;
+    module.starts_with("day").then_some((module, commented))
+}
+
+/// Declares the module for a day in `lib.rs`, by uncommenting its declaration or else by
+/// adding one among the other days. Fails if the module is already declared.
+fn register(lib: &str, day: u32) -> Result<String, String> {
+    let module = format!("day{:02}", day);
+    let declaration = format!("pub mod {};", module);
+    let lines: Vec<&str> = lib.lines().collect();
+
+    let mut insert_at = None;
+    for (i, line) in lines.iter().enumerate() {
+        match day_module(line) {
+            Some((name, false)) if name == module => {
+                return Err(format!("`{}` is already declared in lib.rs", module));
+            }
+            Some((name, true)) if name == module => {
+                let mut lines = lines.clone();
+                lines[i] = &declaration;
+                return Ok(lines.join("\n") + "\n");
+            }
+            Some((name, _)) if name < module.as_str() => insert_at = Some(i + 1),
+            Some(_) => {
+                insert_at.get_or_insert(i);
+            }
+            None => {}
+        }
+    }
+    let Some(i) = insert_at else {
+        return Err("lib.rs declares no other days to add this one beside".to_string());
+    };
+    let mut lines = lines;
+    lines.insert(i, &declaration);
+    Ok(lines.join("\n") + "\n")
+}
+
+fn read(path: &Path) -> Result<String, Failure> {
+    fs::read_to_string(path).map_err(|e| {
+        eprintln!("aoc: {}: {}", path.display(), e);
+        Failure::Failed
+    })
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Failure> {
+    fs::write(path, contents).map_err(|e| {
+        eprintln!("aoc: {}: {}", path.display(), e);
+        Failure::Failed
+    })?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+/// Adds a day to a year crate: its module, instantiated from `src/day-template.rs` and
+/// declared in `lib.rs`, along with an empty example and a stub for the example's answers.
+///
+/// Nothing is written if the day's module already exists. An example or answers that are
+/// already in place are left as they are.
+fn new(year: &Year, day: u32) -> Result<(), Failure> {
+    let src_dir = Path::new(year.manifest_dir).join("src");
+    let module_path = src_dir.join(format!("day{:02}.rs", day));
+    let lib_path = src_dir.join("lib.rs");
+    let examples_dir: PathBuf = [year.manifest_dir, "..", "..", "examples"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(year.year.to_string());
+
+    if module_path.exists() {
+        eprintln!("aoc: {} already exists", module_path.display());
+        return Err(Failure::Failed);
+    }
+    let template = read(&src_dir.join("day-template.rs"))?;
+    let lib = register(&read(&lib_path)?, day).map_err(|e| {
+        eprintln!("aoc: {}", e);
+        Failure::Failed
+    })?;
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&module_path)
+        .and_then(|mut file| file.write_all(instantiate(&template, day).as_bytes()))
+        .map_err(|e| {
+            eprintln!("aoc: {}: {}", module_path.display(), e);
+            Failure::Failed
+        })?;
+    println!("wrote {}", module_path.display());
+    write(&lib_path, &lib)?;
+
+    let example_path = examples_dir.join(format!("day{:02}.txt", day));
+    if !example_path.exists() {
+        write(&example_path, "")?;
+    }
+    let answers_path = examples_dir.join("answers.toml");
+    let answers = if answers_path.exists() {
+        read(&answers_path)?
+    } else {
+        String::new()
+    };
+    let table = format!("[day{:02}]", day);
+    if !answers.lines().any(|line| line.trim() == table) {
+        let separator = if answers.is_empty() { "" } else { "\n" };
+        let stub = format!("{}{}\n# part1 =\n# part2 =\n", separator, table);
+        write(&answers_path, &(answers + &stub))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn instantiates_templates() {
+        let template = "#[aoc_generator(day2, part1, Original)]\n\
+                        fn input_generator(raw_input: &str) -> Input {\n  \
+                          day2(raw_input)\n\
+                        }\n\
+                        \n  \
+                        #[aoc(day2, part1, Original)]\n";
+        assert_eq!(
+            instantiate(template, 15),
+            "#[aoc_generator(day15, part1, Original)]\n\
+             fn input_generator(raw_input: &str) -> Input {\n  \
+               day2(raw_input)\n\
+             }\n\
+             \n  \
+             #[aoc(day15, part1, Original)]\n",
+        );
+    }
+
+    #[test]
+    fn uncomments_declarations() {
+        let lib = "pub mod day01;\n//pub mod day02;\n//pub mod day03;\n\naoc_lib! {}\n";
+        assert_eq!(
+            register(lib, 2).unwrap(),
+            "pub mod day01;\npub mod day02;\n//pub mod day03;\n\naoc_lib! {}\n",
+        );
+    }
+
+    #[test]
+    fn adds_declarations_in_order() {
+        let lib = "pub mod day01;\npub mod day03;\n\naoc_lib! {}\n";
+        assert_eq!(
+            register(lib, 2).unwrap(),
+            "pub mod day01;\npub mod day02;\npub mod day03;\n\naoc_lib! {}\n",
+        );
+        assert_eq!(
+            register(lib, 25).unwrap(),
+            "pub mod day01;\npub mod day03;\npub mod day25;\n\naoc_lib! {}\n",
+        );
+        assert_eq!(
+            register("use a;\n\npub mod day07;\n", 4).unwrap(),
+            "use a;\n\npub mod day04;\npub mod day07;\n",
+        );
+    }
+
+    #[test]
+    fn refuses_declared_days() {
+        assert!(register("pub mod day01;\n", 1).is_err());
+        assert!(register("use a;\n", 1).is_err());
+    }
+}
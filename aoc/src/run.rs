@@ -0,0 +1,84 @@
+//! The `aoc run` subcommand.
+
+use crate::{day, year, Failure};
+use aoc_common::runner::{self, Format};
+
+/// The solutions to run and how to print them, from
+/// `YEAR [DAY] [--part PART] [--variant VARIANT] [--format FORMAT]`.
+struct Selection<'a> {
+    year: &'a str,
+    day: Option<u32>,
+    part: Option<u32>,
+    variant: Option<&'a str>,
+    format: Format,
+}
+
+fn parse<'a>(args: &[&'a str]) -> Result<Selection<'a>, Failure> {
+    let (year, mut args) = match args {
+        [year, args @ ..] => (*year, args),
+        [] => return Err(Failure::Usage),
+    };
+    let mut selection = Selection {
+        year,
+        day: None,
+        part: None,
+        variant: None,
+        format: Format::Table,
+    };
+    if let [day_arg, rest @ ..] = args {
+        if !day_arg.starts_with("--") {
+            selection.day = Some(day(day_arg)?);
+            args = rest;
+        }
+    }
+    while !args.is_empty() {
+        match args {
+            ["--part", part @ ("1" | "2"), rest @ ..] => {
+                selection.part = part.parse().ok();
+                args = rest;
+            }
+            ["--variant", variant, rest @ ..] => {
+                selection.variant = Some(variant);
+                args = rest;
+            }
+            ["--format", format, rest @ ..] => {
+                selection.format = format.parse().map_err(|()| Failure::Usage)?;
+                args = rest;
+            }
+            _ => return Err(Failure::Usage),
+        }
+    }
+    Ok(selection)
+}
+
+/// Runs the selected solutions and prints their answers in the selected format, failing if
+/// any of them gave no answer.
+pub fn run(args: &[&str]) -> Result<(), Failure> {
+    let selection = parse(args)?;
+    let year = year(selection.year)?;
+    let inputs = (year.inputs)();
+    let outcomes: Vec<_> = year
+        .solutions
+        .iter()
+        .filter(|solution| selection.day.is_none_or(|day| solution.day == day))
+        .filter(|solution| selection.part.is_none_or(|part| solution.part == part))
+        .filter(|solution| {
+            selection.variant.is_none_or(|variant| {
+                solution
+                    .variant
+                    .is_some_and(|name| name.eq_ignore_ascii_case(variant))
+            })
+        })
+        .map(|solution| runner::run(year.year, solution, &inputs))
+        .collect();
+    if outcomes.is_empty() {
+        eprintln!("aoc: no solutions match; see `aoc list {}`", year.year);
+        return Err(Failure::Failed);
+    }
+    print!("{}", runner::render(&outcomes, selection.format));
+    if outcomes.iter().all(|outcome| outcome.result.is_ok()) {
+        Ok(())
+    } else {
+        Err(Failure::Failed)
+    }
+}
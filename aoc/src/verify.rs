@@ -0,0 +1,28 @@
+//! The `aoc verify` subcommand.
+
+use crate::{year, Failure, Year, YEARS};
+use aoc_common::runner;
+
+pub fn run(args: &[&str]) -> Result<(), Failure> {
+    match args {
+        [] => {
+            let results: Vec<_> = YEARS.iter().map(verify).collect();
+            results.into_iter().collect()
+        }
+        [year_arg] => verify(year(year_arg)?),
+        _ => Err(Failure::Usage),
+    }
+}
+
+/// Checks every solution of a year against its recorded answer, failing on any mismatch.
+fn verify(year: &Year) -> Result<(), Failure> {
+    let answers = (year.answers)().map_err(|e| {
+        eprintln!("aoc: {}", e);
+        Failure::Failed
+    })?;
+    if runner::verify_all(year.year, year.solutions, &(year.inputs)(), &answers) {
+        Ok(())
+    } else {
+        Err(Failure::Failed)
+    }
+}